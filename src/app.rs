@@ -1,5 +1,11 @@
-use crate::{board::Board, constants::Pages};
-use std::error;
+use crate::{
+    board::{Board, GameStatus},
+    constants::Pages,
+    engine::Engine,
+    move_tree::MoveTree,
+    pieces::PieceColor,
+};
+use std::{error, fs, path::Path};
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
@@ -14,10 +20,59 @@ pub struct App {
     pub current_page: Pages,
     /// Used to show the help popup during the game or in the home menu
     pub show_help_popup: bool,
+    /// the history panel is hidden to give its width to the board
+    pub show_history_panel: bool,
     /// menu current cursor
     pub menu_cursor: u8,
     /// path of the chess engine
     pub chess_engine_path: Option<String>,
+    /// engines playing against each other in the mirror mode
+    pub self_play: Option<SelfPlay>,
+    /// fen typed or pasted to jump to another position, `None` when the input is closed
+    pub fen_input: Option<String>,
+    /// why the last submitted fen was rejected
+    pub fen_input_error: Option<String>,
+    /// moves of the game and the variations explored from it
+    pub analysis: MoveTree,
+}
+
+/// Two engines playing a game against each other, one move per tick
+pub struct SelfPlay {
+    pub white_engine: Engine,
+    pub black_engine: Engine,
+    /// fen the game started from, `None` for the default position
+    pub starting_fen: Option<String>,
+    /// every move of the game in UCI notation
+    pub moves: Vec<String>,
+}
+
+impl SelfPlay {
+    pub fn new(engine_path: &str, starting_fen: Option<String>) -> AppResult<Self> {
+        Ok(Self {
+            white_engine: Engine::new(engine_path)?,
+            black_engine: Engine::new(engine_path)?,
+            starting_fen,
+            moves: vec![],
+        })
+    }
+
+    /// Ask the engine of the side to move for its move and play it on the board
+    pub fn play_move(&mut self, board: &mut Board) -> AppResult<GameStatus> {
+        let engine = match board.player_turn() {
+            PieceColor::White => &self.white_engine,
+            PieceColor::Black => &self.black_engine,
+        };
+
+        match &self.starting_fen {
+            Some(fen) => engine.make_moves_from_position(fen, &self.moves),
+            None => engine.make_moves(&self.moves),
+        }?;
+        let movement = engine.bestmove()?;
+
+        let status = board.self_play_move(&movement)?;
+        self.moves.push(movement);
+        Ok(status)
+    }
 }
 
 impl Default for App {
@@ -27,8 +82,13 @@ impl Default for App {
             board: Board::default(),
             current_page: Pages::Home,
             show_help_popup: false,
+            show_history_panel: true,
             menu_cursor: 0,
             chess_engine_path: None,
+            self_play: None,
+            fen_input: None,
+            fen_input_error: None,
+            analysis: MoveTree::default(),
         }
     }
 }
@@ -46,7 +106,41 @@ impl App {
     }
 
     /// Handles the tick event of the terminal.
-    pub fn tick(&self) {}
+    /// The self-play waits with the error of its engine until they are started again
+    pub fn tick(&mut self) {
+        if let Some(self_play) = &mut self.self_play {
+            if self.board.status() == GameStatus::Ongoing && self.board.engine_error.is_none() {
+                if let Err(error) = self_play.play_move(&mut self.board) {
+                    self.board.engine_error = Some(error.to_string());
+                }
+            }
+        }
+    }
+
+    /// Start the chess engines again after one stopped, the game goes on from the current position
+    pub fn restart_engine(&mut self) {
+        let (Some(self_play), Some(engine_path)) = (&self.self_play, &self.chess_engine_path)
+        else {
+            self.board.restart_engine();
+            return;
+        };
+        match SelfPlay::new(engine_path.trim(), self_play.starting_fen.clone()) {
+            Ok(engines) => {
+                self.self_play = Some(SelfPlay {
+                    moves: self_play.moves.clone(),
+                    ..engines
+                });
+                self.board.engine_error = None;
+            }
+            Err(error) => self.board.engine_error = Some(error.to_string()),
+        }
+    }
+
+    /// Keep playing both sides on this terminal after the chess engine stopped
+    pub fn play_locally(&mut self) {
+        self.self_play = None;
+        self.board.play_locally();
+    }
 
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
@@ -70,8 +164,104 @@ impl App {
     }
 
     pub fn restart(&mut self) {
-        if self.board.is_draw || self.board.is_checkmate {
-            self.board = Board::default()
+        if self.board.is_game_over() {
+            self.board.replace_game(Board::default());
+            // The bot opens the new game when the human plays black
+            self.board.play_bot_turn_if_due();
+            self.analysis = MoveTree::default();
+            if let Some(self_play) = &mut self.self_play {
+                self_play.moves.clear();
+            }
+        }
+    }
+
+    /// Write the ongoing game to `path` so it can be resumed, the save of a finished game is removed
+    pub fn save_game(&self, path: &Path) -> AppResult<()> {
+        if self.board.move_history().is_empty() || self.board.status() != GameStatus::Ongoing {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        fs::write(path, self.board.to_uci_position())?;
+        Ok(())
+    }
+
+    /// Read a game written by `save_game`
+    pub fn load_game(path: &Path) -> AppResult<Board> {
+        Ok(Board::from_uci_position(&fs::read_to_string(path)?)?)
+    }
+
+    /// Open the fen input, only during a game
+    pub fn open_fen_input(&mut self) {
+        if self.current_page == Pages::Solo || self.current_page == Pages::Bot {
+            self.fen_input = Some(String::new());
+            self.fen_input_error = None;
+        }
+    }
+
+    pub fn close_fen_input(&mut self) {
+        self.fen_input = None;
+        self.fen_input_error = None;
+    }
+
+    /// Jump to the typed fen position, an invalid fen keeps the current game and the input open with its error
+    pub fn submit_fen_input(&mut self) {
+        let Some(fen) = &self.fen_input else {
+            return;
+        };
+        let fen = fen.trim().to_string();
+        match self.board.set_position_from_fen(&fen) {
+            Ok(()) => {
+                if let Some(self_play) = &mut self.self_play {
+                    self_play.starting_fen = Some(fen);
+                    self_play.moves.clear();
+                }
+                self.close_fen_input();
+                self.board.play_bot_turn_if_due();
+            }
+            Err(error) => self.fen_input_error = Some(error.to_string()),
+        }
+    }
+
+    /// Record the moves of the board in the analysis tree, after each move played
+    pub fn sync_analysis(&mut self) {
+        let moves = self.board.to_uci_moves();
+        self.analysis.sync(
+            self.board.starting_fen.as_deref(),
+            &moves.split_whitespace().collect::<Vec<_>>(),
+        );
+    }
+
+    /// Move in the analysis tree and show its position, only in a game against no one
+    pub fn navigate_analysis(&mut self, step: fn(&mut MoveTree) -> bool) {
+        if self.current_page != Pages::Solo
+            || self.self_play.is_some()
+            || self.board.is_game_against_bot
+            || self.board.is_promotion
+        {
+            return;
+        }
+        self.sync_analysis();
+        if !step(&mut self.analysis) {
+            return;
+        }
+        if self
+            .board
+            .set_position_from_uci(&self.analysis.uci_position())
+            .is_ok()
+        {
+            let status = self.board.status();
+            self.board.is_checkmate = matches!(status, GameStatus::Checkmate(_));
+            self.board.is_draw = status == GameStatus::Draw;
+        }
+    }
+
+    /// Make the line of the shown position the main line of the analysis
+    pub fn promote_variation(&mut self) {
+        if self.current_page == Pages::Solo {
+            self.sync_analysis();
+            self.analysis.promote_variation();
         }
     }
 
@@ -85,3 +275,243 @@ impl App {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(unix)]
+    use crate::test_utils::fake_engine;
+    use crate::{
+        app::App, board::Board, constants::Pages, move_tree::MoveTree, pieces::PieceColor,
+        test_utils::temp_path,
+    };
+
+    #[test]
+    fn saved_game_reloads_the_same_history() {
+        let path = temp_path("saved-game-test");
+        let mut app = App {
+            board: Board::from_uci_position("startpos moves e2e4 e7e5 e1e2 b8c6").unwrap(),
+            ..Default::default()
+        };
+        app.save_game(&path).unwrap();
+        let board = App::load_game(&path).unwrap();
+        assert_eq!(board.move_history(), app.board.move_history());
+        assert_eq!(board.board(), app.board.board());
+
+        // promotion from a custom position
+        app.board =
+            Board::from_uci_position("fen 8/4P3/8/8/8/k7/8/K7 w - - 0 1 moves e7e8q").unwrap();
+        app.save_game(&path).unwrap();
+        let board = App::load_game(&path).unwrap();
+        assert_eq!(board.move_history(), app.board.move_history());
+        assert_eq!(board.board(), app.board.board());
+        assert_eq!(board.player_turn(), app.board.player_turn());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn finished_game_removes_the_save() {
+        let path = temp_path("finished-game-test");
+        std::fs::write(&path, "startpos moves e2e4").unwrap();
+
+        let app = App {
+            board: Board::from_uci_position("startpos moves f2f3 e7e5 g2g4 d8h4").unwrap(),
+            ..Default::default()
+        };
+        app.save_game(&path).unwrap();
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn fen_input_jumps_to_the_position() {
+        let mut app = App {
+            board: Board::from_uci_position("startpos moves e2e4 e7e5").unwrap(),
+            current_page: Pages::Solo,
+            ..Default::default()
+        };
+
+        app.open_fen_input();
+        app.fen_input = Some(" 4k3/8/8/8/8/8/4P3/4K3 b - - 0 40 ".to_string());
+        app.submit_fen_input();
+        assert_eq!(app.fen_input, None);
+        assert_eq!(app.fen_input_error, None);
+        assert!(app.board.move_history().is_empty());
+        assert_eq!(app.board.player_turn(), PieceColor::Black);
+        assert_eq!(app.board.starting_fullmove_number, 40);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fen_input_gives_the_move_to_the_bot() {
+        let engine_path = fake_engine(
+            "fen-input-engine.sh",
+            "while read line; do\n\
+             case \"$line\" in\n\
+             go*) echo \"bestmove e8d8\";;\n\
+             isready) echo readyok;;\n\
+             esac\n\
+             done\n",
+        );
+
+        let mut app = App {
+            current_page: Pages::Bot,
+            ..Default::default()
+        };
+        app.board.set_engine(engine_path.to_str().unwrap());
+
+        // Black is to move in the typed position, the bot plays it right away
+        app.open_fen_input();
+        app.fen_input = Some("4k3/8/8/8/8/8/4P3/4K3 b - - 0 40".to_string());
+        app.submit_fen_input();
+        assert_eq!(app.board.engine_error, None);
+        assert_eq!(app.board.to_uci_moves(), "e8d8");
+        assert_eq!(app.board.player_turn(), PieceColor::White);
+
+        std::fs::remove_file(engine_path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn self_play_engine_error_keeps_the_app_running() {
+        use crate::app::SelfPlay;
+
+        // an engine closing its pipes after the handshake
+        let engine_path = fake_engine("self-play-engine.sh", "");
+
+        let mut app = App {
+            self_play: Some(SelfPlay::new(engine_path.to_str().unwrap(), None).unwrap()),
+            ..Default::default()
+        };
+        app.tick();
+        assert!(app.running);
+        assert!(app.board.engine_error.is_some());
+        assert!(app.board.move_history().is_empty());
+
+        // the engines can't start again once their file is gone
+        std::fs::remove_file(&engine_path).unwrap();
+        app.chess_engine_path = Some(engine_path.to_str().unwrap().to_string());
+        app.restart_engine();
+        assert!(app.board.engine_error.is_some());
+        app.tick();
+        assert!(app.board.move_history().is_empty());
+
+        app.play_locally();
+        assert!(app.self_play.is_none());
+        assert_eq!(app.board.engine_error, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn self_play_goes_on_until_checkmate() {
+        use crate::{app::SelfPlay, board::GameStatus};
+
+        // both engines play the fool's mate, answering the last move of the position
+        let engine_path = fake_engine(
+            "self-play-mate-engine.sh",
+            "while read line; do\n\
+             case \"$line\" in\n\
+             *f2f3) move=e7e5;;\n\
+             *e7e5) move=g2g4;;\n\
+             *g2g4) move=d8h4;;\n\
+             position*) move=f2f3;;\n\
+             go*) echo \"bestmove $move\";;\n\
+             isready) echo readyok;;\n\
+             esac\n\
+             done\n",
+        );
+
+        let mut app = App {
+            self_play: Some(SelfPlay::new(engine_path.to_str().unwrap(), None).unwrap()),
+            ..Default::default()
+        };
+        for _ in 0..10 {
+            app.tick();
+        }
+        assert_eq!(app.board.engine_error, None);
+        assert_eq!(app.board.to_uci_moves(), "f2f3 e7e5 g2g4 d8h4");
+        assert_eq!(app.board.status(), GameStatus::Checkmate(PieceColor::Black));
+        assert!(app.board.is_checkmate);
+        assert_eq!(app.self_play.unwrap().moves.len(), 4);
+
+        std::fs::remove_file(engine_path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restart_keeps_the_engine_and_the_settings() {
+        let engine_path = fake_engine("restart-engine.sh", "cat > /dev/null\n");
+        let mut app = App {
+            current_page: Pages::Bot,
+            ..Default::default()
+        };
+        app.board.set_engine(engine_path.to_str().unwrap());
+        app.board
+            .set_position_from_uci("startpos moves f2f3 e7e5 g2g4 d8h4")
+            .unwrap();
+        app.board.set_human_color(PieceColor::Black);
+        app.board.confirm_moves = true;
+
+        app.restart();
+        assert!(app.board.is_game_against_bot);
+        assert!(app.board.engine.is_some());
+        assert!(app.board.confirm_moves);
+        assert_eq!(app.board.human_color, PieceColor::Black);
+        // the bot opened the new game from its book
+        assert_eq!(app.board.move_history().len(), 1);
+        assert_eq!(app.board.player_turn(), PieceColor::Black);
+
+        std::fs::remove_file(engine_path).unwrap();
+    }
+
+    #[test]
+    fn invalid_fen_input_keeps_the_game() {
+        let mut app = App {
+            board: Board::from_uci_position("startpos moves e2e4 e7e5").unwrap(),
+            current_page: Pages::Solo,
+            ..Default::default()
+        };
+        let board = app.board.board();
+
+        app.open_fen_input();
+        app.fen_input = Some("8/8 w - - 0 1".to_string());
+        app.submit_fen_input();
+        assert!(app.fen_input.is_some());
+        assert!(app
+            .fen_input_error
+            .as_ref()
+            .is_some_and(|error| error.starts_with("incorrect fen position")));
+        assert_eq!(app.board.board(), board);
+        assert_eq!(app.board.move_history().len(), 2);
+
+        app.close_fen_input();
+        assert_eq!(app.fen_input, None);
+        assert_eq!(app.fen_input_error, None);
+    }
+
+    #[test]
+    fn analysis_navigation() {
+        let mut app = App {
+            board: Board::from_uci_position("startpos moves e2e4 e7e5").unwrap(),
+            current_page: Pages::Solo,
+            ..Default::default()
+        };
+        app.navigate_analysis(MoveTree::back);
+        assert_eq!(app.board.to_uci_moves(), "e2e4");
+
+        // a new move branches off, the previous one stays a variation
+        app.board = Board::from_uci_position("startpos moves e2e4 c7c5").unwrap();
+        app.sync_analysis();
+        app.navigate_analysis(MoveTree::previous_variation);
+        assert_eq!(app.board.to_uci_moves(), "e2e4 e7e5");
+        assert_eq!(app.analysis.variation(), (1, 2));
+        app.navigate_analysis(MoveTree::next_variation);
+        app.promote_variation();
+        assert_eq!(app.analysis.main_line(), ["e2e4", "c7c5"]);
+
+        // no navigation against the bot
+        app.board.is_game_against_bot = true;
+        app.navigate_analysis(MoveTree::back);
+        assert_eq!(app.board.to_uci_moves(), "e2e4 c7c5");
+    }
+}
@@ -1,6 +1,41 @@
+use crate::pieces::PieceType;
 use ratatui::style::Color;
 
+/// Row and column of `Coords::default()`, like an unselected cell.
+/// Such coordinates hold no piece and have no authorized positions, they are never used as an index
 pub const UNDEFINED_POSITION: i8 = -1;
+
+// (row, col) directions used to generate the piece moves
+pub const DIAGONALS: [(i8, i8); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+pub const ORTHOGONALS: [(i8, i8); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+pub const KNIGHT_MOVES: [(i8, i8); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+// Pieces a pawn can be promoted to, in the order of `promotion_cursor`
+pub const PROMOTION_PIECES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+// The bot resigns after this many turns in a row with this material balance or worse
+pub const BOT_RESIGN_MATERIAL: i32 = -9;
+pub const BOT_RESIGN_TURNS: u32 = 3;
+// Time in milliseconds the bot searches for a mate against a lone king, the few moves left allow a deeper search
+pub const BOT_LONE_KING_MOVETIME: u32 = 1000;
+// Plies without a pawn move or a capture after which the game is drawn by the fifty-move rule
+pub const FIFTY_MOVES_PLIES: u32 = 100;
+// Highest skill level of the chess engine, the lowest is 0
+pub const MAX_ENGINE_SKILL_LEVEL: u8 = 20;
+// Initial position, used when no fen is given
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 pub const WHITE: Color = Color::Rgb(160, 160, 160);
 pub const BLACK: Color = Color::Rgb(128, 95, 69);
 
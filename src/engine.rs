@@ -0,0 +1,291 @@
+use crate::error::ChessError;
+use std::{
+    cell::RefCell,
+    env,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+/// Time the engine searches for a move, in milliseconds
+const DEFAULT_MOVETIME: u32 = 100;
+/// Longest wait for a line of the engine, it is considered stuck after it
+const ENGINE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time given to the engine to handle a command before its output is read
+const COMMAND_DELAY: Duration = Duration::from_millis(100);
+
+/// Chess engine speaking UCI in its own process. Its output is read by a thread, so an engine
+/// which stopped or hangs gives an error instead of freezing the interface
+pub struct Engine {
+    process: Child,
+    stdin: RefCell<ChildStdin>,
+    /// lines written by the engine, disconnected once its output is closed
+    lines: Receiver<String>,
+    movetime: u32,
+}
+
+impl Engine {
+    /// Start the engine at `path`, a bare name is looked up in the `PATH`
+    pub fn new(path: &str) -> Result<Self, ChessError> {
+        let executable = find_executable(path)?;
+        let mut process = Command::new(executable)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| ChessError::EngineUnavailable(e.to_string()))?;
+        let (Some(stdin), Some(stdout)) = (process.stdin.take(), process.stdout.take()) else {
+            return Err(ChessError::EngineUnavailable(
+                "can't talk to the engine".to_string(),
+            ));
+        };
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let engine = Self {
+            process,
+            stdin: RefCell::new(stdin),
+            lines,
+            movetime: DEFAULT_MOVETIME,
+        };
+        engine.command("uci")?;
+        Ok(engine)
+    }
+
+    /// Time the engine searches for each move, in milliseconds
+    pub fn movetime(mut self, movetime: u32) -> Self {
+        self.movetime = movetime;
+        self
+    }
+
+    pub fn make_moves(&self, moves: &[String]) -> Result<(), ChessError> {
+        self.write(&format!("position startpos moves {}", moves.join(" ")))
+    }
+
+    pub fn set_position(&self, fen: &str) -> Result<(), ChessError> {
+        self.make_moves_from_position(fen, &[])
+    }
+
+    pub fn make_moves_from_position(&self, fen: &str, moves: &[String]) -> Result<(), ChessError> {
+        self.write(&format!("position fen {} moves {}", fen, moves.join(" ")))
+    }
+
+    /// Best move of the engine for the position it was given, in UCI notation
+    pub fn bestmove(&self) -> Result<String, ChessError> {
+        Ok(self.search()?.0)
+    }
+
+    /// Best move of the engine with the output of its search, which holds its score and line
+    pub fn search(&self) -> Result<(String, String), ChessError> {
+        self.search_with_movetime(self.movetime)
+    }
+
+    /// Like `search`, searching `movetime` milliseconds for this move only
+    pub fn search_with_movetime(&self, movetime: u32) -> Result<(String, String), ChessError> {
+        self.go(&format!("go movetime {}", movetime), movetime)
+    }
+
+    /// Like `search`, searching `depth` plies deep instead of for a time
+    pub fn search_to_depth(&self, depth: u32) -> Result<(String, String), ChessError> {
+        self.go(&format!("go depth {}", depth), 0)
+    }
+
+    /// Start a search with the `go` command and read its output until the best move,
+    /// each line is awaited `movetime` milliseconds longer than the answer to other commands
+    fn go(&self, command: &str, movetime: u32) -> Result<(String, String), ChessError> {
+        self.write(command)?;
+        let mut output = vec![];
+        loop {
+            let line = self.read_line(movetime)?;
+            if let Some(movement) = line.strip_prefix("bestmove") {
+                let movement = movement.split_whitespace().next().unwrap_or_default();
+                return Ok((movement.to_string(), output.join("\n")));
+            }
+            output.push(line);
+        }
+    }
+
+    pub fn set_option(&self, name: &str, value: &str) -> Result<(), ChessError> {
+        self.write(&format!("setoption name {} value {}", name, value))?;
+        let output = self.read_left_output()?;
+        // engines may also write information lines, only these ones reject the option
+        let rejected = output.lines().any(|line| {
+            let line = line.trim();
+            line.starts_with("No such option") || line.starts_with("Unknown option")
+        });
+        if rejected {
+            Err(ChessError::EngineUnavailable(format!(
+                "unknown option '{}'",
+                name
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Send a command and give what the engine wrote until it is ready again
+    pub fn command(&self, command: &str) -> Result<String, ChessError> {
+        self.write(command.trim())?;
+        thread::sleep(COMMAND_DELAY);
+        self.read_left_output()
+    }
+
+    fn read_left_output(&self) -> Result<String, ChessError> {
+        self.write("isready")?;
+        let mut output = vec![];
+        loop {
+            match self.read_line(self.movetime)?.trim() {
+                "readyok" => return Ok(output.join("\n")),
+                line => output.push(line.to_string()),
+            }
+        }
+    }
+
+    fn write(&self, command: &str) -> Result<(), ChessError> {
+        let mut stdin = self.stdin.borrow_mut();
+        writeln!(stdin, "{}", command)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| ChessError::EngineUnavailable(e.to_string()))
+    }
+
+    fn read_line(&self, movetime: u32) -> Result<String, ChessError> {
+        self.lines
+            .recv_timeout(ENGINE_TIMEOUT + Duration::from_millis(movetime.into()))
+            .map_err(|e| {
+                ChessError::EngineUnavailable(match e {
+                    RecvTimeoutError::Timeout => "the engine stopped answering".to_string(),
+                    RecvTimeoutError::Disconnected => "the engine stopped".to_string(),
+                })
+            })
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Path of the engine to start, an error if there is no executable file there
+fn find_executable(path: &str) -> Result<PathBuf, ChessError> {
+    let path = Path::new(path.trim());
+    let candidates: Vec<PathBuf> = if path.components().count() > 1 {
+        vec![path.to_path_buf()]
+    } else {
+        env::var_os("PATH")
+            .map(|dirs| {
+                env::split_paths(&dirs)
+                    .flat_map(|dir| {
+                        [
+                            dir.join(path),
+                            dir.join(path).with_extension(env::consts::EXE_EXTENSION),
+                        ]
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    candidates
+        .into_iter()
+        .find(|candidate| is_executable(candidate))
+        .ok_or_else(|| {
+            ChessError::EngineUnavailable(format!(
+                "no executable chess engine at '{}'",
+                path.display()
+            ))
+        })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(unix)]
+    use crate::test_utils::fake_engine;
+    use crate::{engine::Engine, error::ChessError};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn missing_engine() {
+        assert!(matches!(
+            Engine::new("/nonexistent/chess-tui-engine"),
+            Err(ChessError::EngineUnavailable(_))
+        ));
+        assert!(matches!(
+            Engine::new("chess-tui-engine-nowhere-in-the-path"),
+            Err(ChessError::EngineUnavailable(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stopped_engine() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        // an engine quitting after the handshake, not executable yet
+        let engine_path = fake_engine("stopping-engine.sh", "");
+        fs::set_permissions(&engine_path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(Engine::new(engine_path.to_str().unwrap()).is_err());
+
+        // its end is noticed without waiting
+        fs::set_permissions(&engine_path, fs::Permissions::from_mode(0o755)).unwrap();
+        let engine = Engine::new(engine_path.to_str().unwrap()).unwrap();
+        let start = Instant::now();
+        assert!(matches!(
+            engine.bestmove(),
+            Err(ChessError::EngineUnavailable(_))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        fs::remove_file(engine_path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unknown_option() {
+        let engine_path = fake_engine(
+            "option-engine.sh",
+            "while read line; do\n\
+             case \"$line\" in\n\
+             \"setoption name Threads value 2\") echo \"info string Using 2 threads\";;\n\
+             setoption*) echo \"No such option: Ponies\";;\n\
+             isready) echo readyok;;\n\
+             esac\n\
+             done\n",
+        );
+        let engine = Engine::new(engine_path.to_str().unwrap()).unwrap();
+
+        // an information line doesn't reject the option
+        assert_eq!(engine.set_option("Threads", "2"), Ok(()));
+        assert!(matches!(
+            engine.set_option("Ponies", "3"),
+            Err(ChessError::EngineUnavailable(_))
+        ));
+
+        std::fs::remove_file(engine_path).unwrap();
+    }
+}
@@ -0,0 +1,50 @@
+use crate::constants::MAX_ENGINE_SKILL_LEVEL;
+use std::{error::Error, fmt};
+
+/// Failures of the chess operations, detailed for the callers reacting to them
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChessError {
+    /// the fen position can't be read, with the reason
+    InvalidFen(String),
+    /// the uci position doesn't start from `startpos` or a fen
+    InvalidPosition(String),
+    /// the move isn't written in a known notation
+    InvalidNotation(String),
+    /// the move can't be played by the side to move
+    IllegalMove(String),
+    /// several pieces can play the SAN move
+    AmbiguousSan(String),
+    /// the difficulty is above the highest skill level of the chess engine
+    InvalidDifficulty(u8),
+    /// a line of the config file can't be read
+    InvalidSetting(String),
+    /// the chess engine can't be started or stopped answering
+    EngineUnavailable(String),
+}
+
+impl fmt::Display for ChessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChessError::InvalidFen(reason) => write!(f, "incorrect fen position: {}", reason),
+            ChessError::InvalidPosition(reason) => write!(f, "incorrect uci position: {}", reason),
+            ChessError::InvalidNotation(movement) => {
+                write!(f, "incorrect move notation: '{}'", movement)
+            }
+            ChessError::IllegalMove(movement) => write!(f, "illegal move: '{}'", movement),
+            ChessError::AmbiguousSan(movement) => write!(f, "ambiguous move: '{}'", movement),
+            ChessError::InvalidDifficulty(level) => {
+                write!(
+                    f,
+                    "incorrect difficulty: {}, the highest is {}",
+                    level, MAX_ENGINE_SKILL_LEVEL
+                )
+            }
+            ChessError::InvalidSetting(reason) => write!(f, "incorrect setting: {}", reason),
+            ChessError::EngineUnavailable(reason) => {
+                write!(f, "the chess engine is unavailable: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for ChessError {}
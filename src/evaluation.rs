@@ -0,0 +1,374 @@
+use crate::{
+    board::{Board, Coords},
+    engine::Engine,
+    error::ChessError,
+    pieces::PieceColor,
+    utils::letter_to_col,
+};
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Stylize},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+use std::fmt;
+
+/// Advantage in centipawns at which the evaluation bar is full
+const EVAL_BAR_SATURATION: f64 = 1000.0;
+/// Centipawns counted for a forced mate, minus the number of moves to it
+const MATE_CENTIPAWNS: i32 = 10_000;
+/// Centipawns a move can lose before the coach points out a better one
+const COACH_INACCURACY: i32 = 50;
+/// Centipawns lost by a move the coach calls a mistake
+const COACH_MISTAKE: i32 = 150;
+
+/// Evaluation of a position given by the chess engine, from White's point of view
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Score {
+    Centipawns(i32),
+    /// mate in this many moves, negative when Black mates
+    Mate(i32),
+}
+
+impl Score {
+    /// Read the latest score of the engine output, like `info depth 10 score cp 35 ...`
+    /// the engine gives it from the point of view of `side_to_move`
+    pub fn from_uci_output(output: &str, side_to_move: PieceColor) -> Option<Self> {
+        let line = output.lines().rev().find(|line| line.contains(" score "))?;
+        let mut words = line.split_whitespace().skip_while(|word| *word != "score");
+        words.next();
+        let kind = words.next()?;
+        let value: i32 = words.next()?.parse().ok()?;
+        // mate 0 means the side to move is mated
+        let value = match (kind, value) {
+            ("mate", 0) => -1,
+            _ => value,
+        };
+        let value = match side_to_move {
+            PieceColor::White => value,
+            PieceColor::Black => -value,
+        };
+
+        match kind {
+            "cp" => Some(Score::Centipawns(value)),
+            "mate" => Some(Score::Mate(value)),
+            _ => None,
+        }
+    }
+
+    /// Centipawns from the point of view of `color`, the sooner mates counting the most
+    pub fn centipawns_for(&self, color: PieceColor) -> i32 {
+        let centipawns = match self {
+            Score::Centipawns(cp) => *cp,
+            Score::Mate(moves) if *moves > 0 => MATE_CENTIPAWNS - moves,
+            Score::Mate(moves) => -MATE_CENTIPAWNS - moves,
+        };
+        match color {
+            PieceColor::White => centipawns,
+            PieceColor::Black => -centipawns,
+        }
+    }
+
+    /// Part of the evaluation bar filled by White, from 0 to 1
+    pub fn fill_ratio(&self) -> f64 {
+        match self {
+            Score::Centipawns(cp) => {
+                (0.5 + f64::from(*cp) / (2.0 * EVAL_BAR_SATURATION)).clamp(0.0, 1.0)
+            }
+            Score::Mate(moves) if *moves > 0 => 1.0,
+            Score::Mate(_) => 0.0,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Score::Centipawns(cp) => format!("{:+.1}", f64::from(*cp) / 100.0),
+            Score::Mate(moves) if *moves > 0 => format!("M{}", moves),
+            Score::Mate(moves) => format!("-M{}", -moves),
+        }
+    }
+}
+
+/// Comment of the coach on a move of `color` from the evaluations before and after it,
+/// `best_move` is the move the engine preferred in SAN, `None` if it is the one played
+pub fn coach_comment(
+    before: Score,
+    after: Score,
+    color: PieceColor,
+    best_move: Option<&str>,
+) -> String {
+    let Some(best_move) = best_move else {
+        return "Best move".to_string();
+    };
+    let loss = before.centipawns_for(color) - after.centipawns_for(color);
+    if loss < COACH_INACCURACY {
+        "Good move".to_string()
+    } else if loss < COACH_MISTAKE {
+        format!("Inaccuracy, better was {}", best_move)
+    } else {
+        format!("Mistake, better was {}", best_move)
+    }
+}
+
+/// Moves of the latest principal variation of the engine output, like `... pv g1f3 d7d5`
+pub fn principal_variation(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .rev()
+        .find(|line| line.contains(" pv "))
+        .map(|line| {
+            line.split_whitespace()
+                .skip_while(|word| *word != "pv")
+                .skip(1)
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Cells of a move in UCI notation like e2e4 or e7e8q, `None` if it doesn't name two cells
+pub fn uci_move_coords(movement: &str) -> Option<(Coords, Coords)> {
+    let chars: Vec<char> = movement.chars().collect();
+    if !(4..=5).contains(&chars.len()) {
+        return None;
+    }
+    let cell = |file: char, rank: char| {
+        if !('a'..='h').contains(&file) {
+            return None;
+        }
+        let coords = Coords::new(8 - rank.to_digit(10)? as i8, letter_to_col(Some(file)));
+        coords.is_valid().then_some(coords)
+    };
+    Some((cell(chars[0], chars[1])?, cell(chars[2], chars[3])?))
+}
+
+/// Result of the analysis of a position by the chess engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    pub score: Option<Score>,
+    /// principal variation in standard algebraic notation, the best move first
+    pub line: Vec<String>,
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let score = self.score.map(|score| score.label());
+        writeln!(f, "evaluation: {}", score.as_deref().unwrap_or("-"))?;
+        writeln!(
+            f,
+            "best move: {}",
+            self.line.first().map_or("-", String::as_str)
+        )?;
+        write!(f, "line: {}", self.line.join(" "))
+    }
+}
+
+/// Let the engine search the `fen` position to `depth` plies, for a use without the interface
+pub fn analyse_fen(engine_path: &str, fen: &str, depth: u32) -> Result<Analysis, ChessError> {
+    let board = Board::from_fen(fen)?;
+    let engine = Engine::new(engine_path)?;
+    engine.set_position(fen)?;
+    let (movement, output) = engine.search_to_depth(depth)?;
+
+    // an engine giving no line still gives its best move
+    let mut line = principal_variation(&output);
+    if line.is_empty() && !movement.is_empty() && movement != "(none)" {
+        line.push(movement);
+    }
+
+    Ok(Analysis {
+        score: Score::from_uci_output(&output, board.player_turn()),
+        line: board.uci_moves_to_san(&line)?,
+    })
+}
+
+/// Vertical bar filled by White from the bottom proportionally to its advantage
+pub fn render_evaluation_bar(score: Score, area: Rect, frame: &mut Frame) {
+    let white_height = (score.fill_ratio() * f64::from(area.height)).round() as u16;
+    let black_area = Rect {
+        height: area.height - white_height,
+        ..area
+    };
+    let white_area = Rect {
+        y: area.y + black_area.height,
+        height: white_height,
+        ..area
+    };
+
+    frame.render_widget(Block::default().bg(Color::DarkGray), black_area);
+    frame.render_widget(Block::default().bg(Color::White), white_area);
+
+    // the score is written on the side having the advantage
+    let label_area = if score.fill_ratio() >= 0.5 {
+        Rect {
+            y: area.y + area.height.saturating_sub(1),
+            height: area.height.min(1),
+            ..area
+        }
+    } else {
+        Rect {
+            height: area.height.min(1),
+            ..area
+        }
+    };
+    let label = Paragraph::new(score.label())
+        .alignment(Alignment::Center)
+        .fg(Color::Red);
+    frame.render_widget(label, label_area);
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(unix)]
+    use crate::test_utils::fake_engine;
+    use crate::{
+        board::Coords,
+        error::ChessError,
+        evaluation::{analyse_fen, coach_comment, principal_variation, uci_move_coords, Score},
+        pieces::PieceColor,
+    };
+
+    #[test]
+    fn fill_ratio_centipawns() {
+        assert_eq!(Score::Centipawns(0).fill_ratio(), 0.5);
+        assert_eq!(Score::Centipawns(500).fill_ratio(), 0.75);
+        assert_eq!(Score::Centipawns(-500).fill_ratio(), 0.25);
+    }
+
+    #[test]
+    fn fill_ratio_saturation() {
+        assert_eq!(Score::Centipawns(1000).fill_ratio(), 1.0);
+        assert_eq!(Score::Centipawns(5000).fill_ratio(), 1.0);
+        assert_eq!(Score::Centipawns(-5000).fill_ratio(), 0.0);
+    }
+
+    #[test]
+    fn fill_ratio_mate() {
+        assert_eq!(Score::Mate(3).fill_ratio(), 1.0);
+        assert_eq!(Score::Mate(-2).fill_ratio(), 0.0);
+    }
+
+    #[test]
+    fn score_from_uci_output() {
+        let output = "info depth 1 score cp 12 nodes 20 pv e2e4\n\
+                      info depth 2 score cp -40 nodes 80 pv e7e5\n\
+                      bestmove e7e5";
+
+        assert_eq!(
+            Score::from_uci_output(output, PieceColor::White),
+            Some(Score::Centipawns(-40))
+        );
+        assert_eq!(
+            Score::from_uci_output(output, PieceColor::Black),
+            Some(Score::Centipawns(40))
+        );
+        assert_eq!(
+            Score::from_uci_output("info depth 5 score mate 2 pv d8h4", PieceColor::Black),
+            Some(Score::Mate(-2))
+        );
+        assert_eq!(Score::from_uci_output("readyok", PieceColor::White), None);
+    }
+
+    #[test]
+    fn principal_variation_first_move() {
+        let output = "info depth 1 score cp 20 nodes 20 pv e2e4\n\
+                      info depth 12 score cp 30 nodes 9000 pv g1f3 d7d5 c2c4\n\
+                      bestmove g1f3 ponder d7d5";
+
+        let pv = principal_variation(output);
+        assert_eq!(pv, ["g1f3", "d7d5", "c2c4"]);
+        assert_eq!(
+            uci_move_coords(&pv[0]),
+            Some((Coords::new(7, 6), Coords::new(5, 5)))
+        );
+        assert_eq!(
+            uci_move_coords("a7a8q"),
+            Some((Coords::new(1, 0), Coords::new(0, 0)))
+        );
+        assert_eq!(uci_move_coords("e9e4"), None);
+        assert_eq!(uci_move_coords("(none)"), None);
+        assert!(principal_variation("bestmove e2e4").is_empty());
+    }
+
+    #[test]
+    fn coach_comments() {
+        let white = PieceColor::White;
+        let black = PieceColor::Black;
+        assert_eq!(
+            coach_comment(Score::Centipawns(30), Score::Centipawns(25), white, None),
+            "Best move"
+        );
+        assert_eq!(
+            coach_comment(
+                Score::Centipawns(30),
+                Score::Centipawns(0),
+                white,
+                Some("Nf3")
+            ),
+            "Good move"
+        );
+        assert_eq!(
+            coach_comment(
+                Score::Centipawns(30),
+                Score::Centipawns(-60),
+                white,
+                Some("Nf3")
+            ),
+            "Inaccuracy, better was Nf3"
+        );
+        // the scores are from White's point of view, Black lost 3 pawns
+        assert_eq!(
+            coach_comment(
+                Score::Centipawns(-20),
+                Score::Centipawns(280),
+                black,
+                Some("Qxd4")
+            ),
+            "Mistake, better was Qxd4"
+        );
+        // missing a mate
+        assert_eq!(
+            coach_comment(Score::Mate(2), Score::Centipawns(900), white, Some("Qh7+")),
+            "Mistake, better was Qh7+"
+        );
+        assert!(Score::Mate(1).centipawns_for(white) > Score::Mate(3).centipawns_for(white));
+        assert!(Score::Mate(-1).centipawns_for(black) > Score::Mate(-3).centipawns_for(black));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn analysis_of_a_fen() {
+        let engine_path = fake_engine(
+            "analysis-engine.sh",
+            "while read line; do\n\
+             case \"$line\" in\n\
+             isready) echo readyok;;\n\
+             \"go depth 5\") echo \"info depth 5 score cp 35 pv g1f3 d7d5 c2c4\"; echo \"bestmove g1f3 ponder d7d5\";;\n\
+             \"go depth 3\") sleep 1; echo \"bestmove e2e4\";;\n\
+             esac\n\
+             done\n",
+        );
+        let engine_path = engine_path.to_str().unwrap();
+
+        let analysis = analyse_fen(engine_path, crate::constants::START_FEN, 5).unwrap();
+        assert_eq!(analysis.score, Some(Score::Centipawns(35)));
+        assert_eq!(analysis.line, ["Nf3", "d5", "c4"]);
+        assert!(analysis.to_string().contains("Nf3 d5 c4"));
+
+        // the search is awaited until the best move, which is the line without a principal variation
+        let analysis = analyse_fen(engine_path, crate::constants::START_FEN, 3).unwrap();
+        assert_eq!(analysis.score, None);
+        assert_eq!(analysis.line, ["e4"]);
+
+        assert!(matches!(
+            analyse_fen(engine_path, "8/8 w - - 0 1", 5),
+            Err(ChessError::InvalidFen(_))
+        ));
+        assert!(matches!(
+            analyse_fen("/no/chess/engine", crate::constants::START_FEN, 5),
+            Err(ChessError::EngineUnavailable(_))
+        ));
+
+        std::fs::remove_file(engine_path).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    board::Board, constants::MAX_ENGINE_SKILL_LEVEL, error::ChessError, pieces::PieceColor,
+    theme::Theme,
+};
+
+/// Settings of a game, `build` checks them and sets the board up in the right order
+#[derive(Default)]
+pub struct GameBuilder {
+    fen: Option<String>,
+    engine_path: Option<String>,
+    human_color: Option<PieceColor>,
+    theme: Option<Theme>,
+    difficulty: Option<u8>,
+}
+
+impl GameBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Chess engine playing the other side
+    pub fn engine(mut self, engine_path: &str) -> Self {
+        self.engine_path = Some(engine_path.to_string());
+        self
+    }
+
+    /// Position the game starts from instead of the initial one
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_fen(mut self, fen: &str) -> Self {
+        self.fen = Some(fen.trim().to_string());
+        self
+    }
+
+    /// Side of the human, the engine plays the other one
+    pub fn play_as(mut self, color: PieceColor) -> Self {
+        self.human_color = Some(color);
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Skill level of the chess engine, from 0 to `MAX_ENGINE_SKILL_LEVEL`
+    pub fn difficulty(mut self, skill_level: u8) -> Self {
+        self.difficulty = Some(skill_level);
+        self
+    }
+
+    /// Board ready to be played, the engine already played its move if it starts
+    pub fn build(self) -> Result<Board, ChessError> {
+        if let Some(skill_level) = self
+            .difficulty
+            .filter(|&level| level > MAX_ENGINE_SKILL_LEVEL)
+        {
+            return Err(ChessError::InvalidDifficulty(skill_level));
+        }
+
+        let mut board = match &self.fen {
+            Some(fen) => Board::start_from_fen(fen)?,
+            None => Board::default(),
+        };
+        if let Some(theme) = self.theme {
+            board.theme = theme;
+        }
+        if let Some(color) = self.human_color {
+            board.set_human_color(color);
+        }
+        board.engine_skill_level = self.difficulty;
+
+        // the engine comes last, it plays right away when it has the move
+        if let Some(engine_path) = &self.engine_path {
+            board.set_engine(engine_path);
+            if let Some(error) = board.engine_error.take() {
+                return Err(ChessError::EngineUnavailable(error));
+            }
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(unix)]
+    use crate::test_utils::fake_engine;
+    use crate::{error::ChessError, game_builder::GameBuilder, pieces::PieceColor, theme::Theme};
+
+    #[test]
+    fn default_game() {
+        let board = GameBuilder::new().build().unwrap();
+        assert!(!board.is_game_against_bot);
+        assert_eq!(board.human_color, PieceColor::White);
+        assert_eq!(board.player_turn(), PieceColor::White);
+        assert_eq!(board.engine_skill_level, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn game_against_an_engine_from_a_fen() {
+        // an engine playing e7e5 only once its skill level is set
+        let engine_path = fake_engine(
+            "builder-engine.sh",
+            "while read line; do\n\
+             case \"$line\" in\n\
+             \"setoption name Skill Level value 5\") skill=5;;\n\
+             isready) echo readyok;;\n\
+             go*) if [ \"$skill\" = 5 ]; then echo \"bestmove e7e5\"; else echo \"bestmove e7e6\"; fi;;\n\
+             esac\n\
+             done\n",
+        );
+
+        let board = GameBuilder::new()
+            .from_fen("rnbqkbnr/pppppppp/8/8/8/P7/1PPPPPPP/RNBQKBNR b KQkq - 0 1")
+            .play_as(PieceColor::White)
+            .theme(Theme {
+                ascii_only: true,
+                ..Default::default()
+            })
+            .difficulty(5)
+            .engine(engine_path.to_str().unwrap())
+            .build()
+            .unwrap();
+
+        assert!(board.is_game_against_bot);
+        assert!(board.engine.is_some());
+        assert!(board.theme.ascii_only);
+        assert_eq!(board.engine_skill_level, Some(5));
+        assert_eq!(board.human_color, PieceColor::White);
+        assert_eq!(board.to_uci_moves(), "e7e5");
+        assert_eq!(board.player_turn(), PieceColor::White);
+
+        std::fs::remove_file(engine_path).unwrap();
+    }
+
+    #[test]
+    fn invalid_settings() {
+        assert_eq!(
+            GameBuilder::new().difficulty(21).build().err(),
+            Some(ChessError::InvalidDifficulty(21))
+        );
+        assert!(matches!(
+            GameBuilder::new().from_fen("8/8 w - - 0 1").build(),
+            Err(ChessError::InvalidFen(_))
+        ));
+    }
+}
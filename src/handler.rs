@@ -1,21 +1,45 @@
 use crate::{
     app::{App, AppResult},
     constants::Pages,
+    move_tree::MoveTree,
 };
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    // the fen input takes every key while it is open
+    if let Some(fen) = &mut app.fen_input {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.quit()
+            }
+            KeyCode::Char(c) => fen.push(c),
+            KeyCode::Backspace => {
+                fen.pop();
+            }
+            KeyCode::Enter => app.submit_fen_input(),
+            KeyCode::Esc => app.close_fen_input(),
+            _ => {}
+        }
+        return Ok(());
+    }
     match key_event.code {
         // Exit application on `q`
         KeyCode::Char('q') => {
             app.quit();
         }
         // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
-            }
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit();
+        }
+        // Choices offered when the chess engine stopped
+        KeyCode::Char('s') | KeyCode::Char('S') if app.board.engine_error.is_some() => {
+            app.restart_engine()
+        }
+        KeyCode::Char('p') | KeyCode::Char('P') if app.board.engine_error.is_some() => {
+            app.play_locally()
         }
         // Counter handlers
         KeyCode::Right | KeyCode::Char('l') => app.board.cursor_right(),
@@ -36,24 +60,39 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         }
         KeyCode::Char(' ') | KeyCode::Enter => {
             if app.current_page != Pages::Home {
-                app.board.select_cell()
+                // the engines play both sides in the mirror mode
+                if app.self_play.is_none() {
+                    app.board.select_cell();
+                    app.sync_analysis();
+                }
             } else {
                 app.menu_select()
             }
         }
-        KeyCode::Char('?') => {
-            if app.current_page != Pages::Credit {
-                app.toggle_help_popup();
-            }
+        KeyCode::Char('?') if app.current_page != Pages::Credit => {
+            app.toggle_help_popup();
         }
         KeyCode::Char('r') => app.restart(),
+        KeyCode::Char('f') => app.open_fen_input(),
+        KeyCode::Char('i') => app.show_history_panel = !app.show_history_panel,
+        KeyCode::Char('x') if app.board.is_game_against_bot => app.board.swap_sides(),
+        // Analysis of the variations
+        KeyCode::Char('<') => app.navigate_analysis(MoveTree::back),
+        KeyCode::Char('>') => app.navigate_analysis(MoveTree::forward),
+        KeyCode::Char('[') => app.navigate_analysis(MoveTree::previous_variation),
+        KeyCode::Char(']') => app.navigate_analysis(MoveTree::next_variation),
+        KeyCode::Char('m') => app.promote_variation(),
         KeyCode::Esc => {
             if app.show_help_popup {
                 app.show_help_popup = false;
             } else if app.current_page == Pages::Credit {
                 app.current_page = Pages::Home;
             }
-            app.board.unselect_cell();
+            if app.board.pending_move.is_some() {
+                app.board.cancel_pending_move();
+            } else {
+                app.board.unselect_cell();
+            }
         }
         KeyCode::Backspace => {
             // if app.current_page == Pages::Solo
@@ -25,5 +25,39 @@ pub mod constants;
 // Utils methods for the board
 pub mod utils;
 
+// Fixtures shared by the tests
+#[cfg(test)]
+mod test_utils;
+
 // popups render methods
 pub mod popups;
+
+// Board colors and highlight styles
+pub mod theme;
+
+// Engine evaluation of the position
+pub mod evaluation;
+
+// Errors of the chess operations
+pub mod error;
+
+// Notifications of the moves played on the board
+pub mod observer;
+
+// Step by step configuration of a game
+pub mod game_builder;
+
+// Opening names and moves keyed by position
+pub mod opening_book;
+
+// Preferences saved in the config file
+pub mod settings;
+
+// SVG images of the positions, to share the games
+pub mod svg;
+
+// Variations of the game explored in the analysis
+pub mod move_tree;
+
+// Chess engine running in its own process, spoken to in UCI
+pub mod engine;
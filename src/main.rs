@@ -1,28 +1,121 @@
 #[cfg(feature = "chess-tui")]
 extern crate chess_tui;
 
-use chess_tui::app::{App, AppResult};
-use chess_tui::board::Board;
+use chess_tui::app::{App, AppResult, SelfPlay};
+use chess_tui::board::{Board, HistoryNotation, Orientation, Rotation};
+use chess_tui::constants::{Pages, START_FEN};
+use chess_tui::error::ChessError;
+use chess_tui::evaluation::analyse_fen;
 use chess_tui::event::{Event, EventHandler};
 use chess_tui::handler::handle_key_events;
+use chess_tui::pieces::PieceColor;
+use chess_tui::settings::Settings;
+use chess_tui::svg::write_game_frames;
+use chess_tui::theme::{CheckHighlight, PieceStyle, Theme};
 use chess_tui::tui::Tui;
 use clap::Parser;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
-use std::fs::{self, File};
 use std::io::{self, Write};
+use std::path::PathBuf;
 
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Path for the chess engine
+    /// Path for the chess engine, remembered in the config file
     #[arg(short, long, default_value = "")]
     engine_path: String,
 
     /// Fen to start the game from
-    #[arg(short, long, default_value = "")]
+    #[arg(short, long, visible_alias = "from-fen", default_value = "")]
     fen_position: String,
+
+    /// Side playing the first move, like `black` for a puzzle posed to Black, the fen gives it otherwise
+    #[arg(long)]
+    side_to_move: Option<PieceColor>,
+
+    /// How the king is highlighted when checked
+    #[arg(long, value_enum)]
+    check_highlight: Option<CheckHighlight>,
+
+    /// Play with the black pieces, drawn at the bottom of the board with `--orientation follow-player`
+    #[arg(long)]
+    black: bool,
+
+    /// Side drawn at the bottom of the board, White unless the config file says otherwise
+    #[arg(long, value_enum)]
+    orientation: Option<Orientation>,
+
+    /// How the moves are written in the history panel
+    #[arg(long, value_enum)]
+    notation: Option<HistoryNotation>,
+
+    /// Ask for a confirmation before playing each move, against misclicks.
+    /// Like the other switches, `--confirm-moves false` turns it off when the config file turns it on
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    confirm_moves: Option<bool>,
+
+    /// Explain why an illegal move can't be played, for beginners
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    explain_illegal_moves: Option<bool>,
+
+    /// Promote the pawns to queens without asking
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    auto_queen: Option<bool>,
+
+    /// Start the promotion popup on the piece chosen for the previous promotion
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    remember_promotion_choice: Option<bool>,
+
+    /// Clockwise rotation of the board on the screen, after the orientation
+    #[arg(long, value_enum)]
+    rotation: Option<Rotation>,
+
+    /// How the pieces are drawn, the borders are drawn with ASCII characters too for `ascii`
+    #[arg(long, value_enum)]
+    piece_style: Option<PieceStyle>,
+
+    /// Only use ASCII characters to draw the pieces and borders, like `--piece-style ascii`
+    #[arg(long)]
+    no_unicode: bool,
+
+    /// The terminal draws the chess glyphs two cells wide, the history columns are aligned accordingly
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    wide_glyphs: Option<bool>,
+
+    /// Let the chess engine resign when it is hopelessly behind in material
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    bot_resigns: Option<bool>,
+
+    /// Don't end the game on the fifty-move rule or a threefold repetition
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    no_auto_draws: Option<bool>,
+
+    /// Comment each of your moves against the bot with the chess engine's evaluation
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    coach: Option<bool>,
+
+    /// Write the settings of this session, with the ones given on the command line, to the config file
+    #[arg(long)]
+    save_settings: bool,
+
+    /// File where the ongoing game is saved on exit, and resumed from on the next launch
+    #[arg(long)]
+    autosave: Option<PathBuf>,
+
+    /// Directory where an SVG image of each position of the game is written on exit, to make a GIF
+    #[arg(long)]
+    svg_frames: Option<PathBuf>,
+
+    /// Let two instances of the chess engine play against each other
+    #[arg(long)]
+    mirror: bool,
+
+    /// Print the engine's evaluation and best line for the fen position searched to this depth, then exit.
+    /// Exits with 2 for an incorrect fen and 3 when the engine is unavailable
+    #[arg(long)]
+    depth: Option<u32>,
     // /// Pgn to start the game from
     // #[arg(short, long, default_value = "")]
     // pgn_position: String,
@@ -34,36 +127,129 @@ fn main() -> AppResult<()> {
 
     let config_path = dirs::config_dir().unwrap().join("chess-tui").join("config");
 
+    let (mut settings, errors) = Settings::load(&config_path)?;
+    for error in errors {
+        eprintln!("Skipped in {}: {}", config_path.display(), error);
+    }
     if !args.engine_path.is_empty() {
-        if !config_path.exists() {
-            if !config_path.parent().unwrap().exists() {
-                fs::create_dir(config_path.parent().unwrap())?;
+        settings.engine_path = Some(args.engine_path.trim().to_string());
+        settings.save(&config_path)?;
+    }
+
+    if let Some(depth) = args.depth {
+        let fen = match args.fen_position.trim() {
+            "" => START_FEN,
+            fen => fen,
+        };
+        let analysis = match &settings.engine_path {
+            Some(engine_path) => analyse_fen(engine_path, fen, depth),
+            None => Err(ChessError::EngineUnavailable(
+                "no chess engine path, set it using chess-tui -e".to_string(),
+            )),
+        };
+        match analysis {
+            Ok(analysis) => {
+                println!("{}", analysis);
+                return Ok(());
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(match error {
+                    ChessError::EngineUnavailable(_) => 3,
+                    _ => 2,
+                });
             }
-            File::create(&config_path)?;
         }
+    }
 
-        let mut file = File::create(&config_path)?;
-        file.write_all(args.engine_path.as_bytes())?;
+    // The command line takes over the config file for this session
+    if let Some(check_highlight) = args.check_highlight {
+        settings.check_highlight = check_highlight;
+    }
+    if let Some(orientation) = args.orientation {
+        settings.orientation = orientation;
+    }
+    if let Some(notation) = args.notation {
+        settings.notation = notation;
+    }
+    if let Some(rotation) = args.rotation {
+        settings.rotation = rotation;
+    }
+    if let Some(piece_style) = args.piece_style {
+        settings.piece_style = piece_style;
+    }
+    if args.no_unicode {
+        settings.piece_style = PieceStyle::Ascii;
+    }
+    if let Some(wide_glyphs) = args.wide_glyphs {
+        settings.wide_glyphs = wide_glyphs;
+    }
+    if let Some(confirm_moves) = args.confirm_moves {
+        settings.confirm_moves = confirm_moves;
+    }
+    if let Some(auto_queen) = args.auto_queen {
+        settings.auto_queen = auto_queen;
+    }
+    if let Some(remember_promotion_choice) = args.remember_promotion_choice {
+        settings.remember_promotion_choice = remember_promotion_choice;
+    }
+    if let Some(explain_illegal_moves) = args.explain_illegal_moves {
+        settings.explain_illegal_moves = explain_illegal_moves;
+    }
+    if let Some(bot_resigns) = args.bot_resigns {
+        settings.bot_resigns = bot_resigns;
+    }
+    if let Some(no_auto_draws) = args.no_auto_draws {
+        settings.no_auto_draws = no_auto_draws;
+    }
+    if let Some(coach) = args.coach {
+        settings.coach = coach;
+    }
+    if args.save_settings {
+        settings.save(&config_path)?;
     }
 
     // Create an application.
     let mut app = App::default();
     if !args.fen_position.is_empty() {
-        app.board = Board::from_fen(args.fen_position.trim())?;
+        app.board = Board::start_from_fen(args.fen_position.trim())?;
+    }
+    if let Some(side_to_move) = args.side_to_move {
+        app.board.set_player_turn(side_to_move);
+    }
+    if let Some(path) = args.autosave.as_ref().filter(|path| path.exists()) {
+        print!(
+            "A saved game was found in {}, resume it? [y/N] ",
+            path.display()
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if answer.trim().eq_ignore_ascii_case("y") {
+            app.board = App::load_game(path)?;
+            app.current_page = Pages::Solo;
+        }
+    }
+    settings.apply(&mut app.board);
+    app.board.theme.truecolor = Theme::detect_truecolor();
+    if args.black {
+        app.board.set_human_color(PieceColor::Black);
     }
     // if !args.pgn_position.is_empty() {
     //     app.board = Board::pgn_import(args.fen_position.trim())?;
     // }
 
     // We store the chess engine path if there is one
-    if let Ok(content) = fs::read_to_string(config_path) {
-        if content.trim().is_empty() {
-            app.chess_engine_path = None
-        } else {
-            app.chess_engine_path = Some(content)
-        }
-    } else {
-        println!("Error reading the file or the file does not exist");
+    app.chess_engine_path = settings.engine_path.clone();
+
+    if args.mirror {
+        let Some(engine_path) = &app.chess_engine_path else {
+            return Err("The mirror mode needs a chess engine, set it using chess-tui -e".into());
+        };
+        let starting_fen =
+            (!args.fen_position.is_empty()).then(|| args.fen_position.trim().to_string());
+        app.self_play = Some(SelfPlay::new(engine_path.trim(), starting_fen)?);
+        app.current_page = Pages::Solo;
     }
 
     // Initialize the terminal user interface.
@@ -89,5 +275,12 @@ fn main() -> AppResult<()> {
 
     // Exit the user interface.
     tui.exit()?;
+
+    if let Some(path) = &args.autosave {
+        app.save_game(path)?;
+    }
+    if let Some(dir) = &args.svg_frames {
+        write_game_frames(&app.board, dir)?;
+    }
     Ok(())
 }
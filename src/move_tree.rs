@@ -0,0 +1,234 @@
+/// A move of the tree in UCI notation, its continuations come first for the main line
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    movement: String,
+    parent: usize,
+    children: Vec<usize>,
+}
+
+/// The moves of a game with their alternative continuations, unlike `move_history` the moves
+/// taken back aren't lost. The position of a node is the one reached by the moves from the root
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveTree {
+    /// fen of the root position, `None` for the default position
+    starting_fen: Option<String>,
+    /// the root is the first node, it has no move
+    nodes: Vec<Node>,
+    current: usize,
+}
+
+impl Default for MoveTree {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl MoveTree {
+    pub fn new(starting_fen: Option<String>) -> Self {
+        Self {
+            starting_fen,
+            nodes: vec![Node {
+                movement: String::new(),
+                parent: 0,
+                children: vec![],
+            }],
+            current: 0,
+        }
+    }
+
+    /// Play a move from the current node, it branches off in a new variation unless it was already played
+    pub fn play(&mut self, movement: &str) {
+        let existing = self.nodes[self.current]
+            .children
+            .iter()
+            .find(|child| self.nodes[**child].movement == movement);
+        self.current = match existing {
+            Some(child) => *child,
+            None => {
+                self.nodes.push(Node {
+                    movement: movement.to_string(),
+                    parent: self.current,
+                    children: vec![],
+                });
+                let child = self.nodes.len() - 1;
+                self.nodes[self.current].children.push(child);
+                child
+            }
+        };
+    }
+
+    /// Follow the moves of a game from the root, the tree starts over for another starting position
+    pub fn sync(&mut self, starting_fen: Option<&str>, moves: &[&str]) {
+        if self.starting_fen.as_deref() != starting_fen {
+            *self = Self::new(starting_fen.map(String::from));
+        }
+        self.current = 0;
+        for movement in moves {
+            self.play(movement);
+        }
+    }
+
+    /// Go to the previous move, false at the root
+    pub fn back(&mut self) -> bool {
+        if self.current == 0 {
+            return false;
+        }
+        self.current = self.nodes[self.current].parent;
+        true
+    }
+
+    /// Go to the main continuation of the current move, false at the end of the line
+    pub fn forward(&mut self) -> bool {
+        match self.nodes[self.current].children.first() {
+            Some(child) => {
+                self.current = *child;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Go to the next alternative of the current move, false without one
+    pub fn next_variation(&mut self) -> bool {
+        self.switch_variation(1)
+    }
+
+    /// Go to the previous alternative of the current move, false without one
+    pub fn previous_variation(&mut self) -> bool {
+        self.switch_variation(-1)
+    }
+
+    fn switch_variation(&mut self, direction: isize) -> bool {
+        let (index, count) = self.variation();
+        if count < 2 {
+            return false;
+        }
+        let index = (index as isize - 1 + direction).rem_euclid(count as isize) as usize;
+        self.current = self.nodes[self.nodes[self.current].parent].children[index];
+        true
+    }
+
+    /// Make the line of the current move the main line, at each of its moves
+    pub fn promote_variation(&mut self) {
+        let mut node = self.current;
+        while node != 0 {
+            let parent = self.nodes[node].parent;
+            let children = &mut self.nodes[parent].children;
+            if let Some(index) = children.iter().position(|child| *child == node) {
+                children.remove(index);
+                children.insert(0, node);
+            }
+            node = parent;
+        }
+    }
+
+    /// Rank of the current move among the alternatives played from the same position,
+    /// from 1, and their number. The root is the only one of its kind
+    pub fn variation(&self) -> (usize, usize) {
+        if self.current == 0 {
+            return (1, 1);
+        }
+        let siblings = &self.nodes[self.nodes[self.current].parent].children;
+        let index = siblings
+            .iter()
+            .position(|child| *child == self.current)
+            .unwrap_or_default();
+        (index + 1, siblings.len())
+    }
+
+    /// Moves from the root to the current move
+    pub fn path(&self) -> Vec<String> {
+        let mut path = vec![];
+        let mut node = self.current;
+        while node != 0 {
+            path.push(self.nodes[node].movement.clone());
+            node = self.nodes[node].parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Moves of the main line, from the root to its end
+    pub fn main_line(&self) -> Vec<String> {
+        let mut line = vec![];
+        let mut node = 0;
+        while let Some(child) = self.nodes[node].children.first() {
+            line.push(self.nodes[*child].movement.clone());
+            node = *child;
+        }
+        line
+    }
+
+    /// The current position as an UCI position command, to be read with `Board::from_uci_position`
+    pub fn uci_position(&self) -> String {
+        let start = match &self.starting_fen {
+            Some(fen) => format!("fen {}", fen),
+            None => "startpos".to_string(),
+        };
+        let path = self.path();
+        if path.is_empty() {
+            start
+        } else {
+            format!("{} moves {}", start, path.join(" "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, move_tree::MoveTree};
+
+    #[test]
+    fn tree_with_two_variations() {
+        let mut tree = MoveTree::default();
+        tree.sync(None, &["e2e4", "e7e5", "g1f3"]);
+        assert_eq!(tree.path(), ["e2e4", "e7e5", "g1f3"]);
+
+        // branch off after e4 with the Sicilian
+        assert!(tree.back());
+        assert!(tree.back());
+        tree.play("c7c5");
+        tree.play("g1f3");
+        assert_eq!(tree.path(), ["e2e4", "c7c5", "g1f3"]);
+        assert_eq!(tree.main_line(), ["e2e4", "e7e5", "g1f3"]);
+
+        // the sibling of c5 is e5, whose line is kept
+        assert!(tree.back());
+        assert_eq!(tree.variation(), (2, 2));
+        assert!(tree.previous_variation());
+        assert_eq!(tree.path(), ["e2e4", "e7e5"]);
+        assert!(tree.forward());
+        assert_eq!(tree.path(), ["e2e4", "e7e5", "g1f3"]);
+        assert!(!tree.forward());
+        assert!(!tree.next_variation());
+
+        // the Sicilian becomes the main line
+        tree.back();
+        tree.next_variation();
+        tree.forward();
+        tree.promote_variation();
+        assert_eq!(tree.main_line(), ["e2e4", "c7c5", "g1f3"]);
+        assert_eq!(tree.variation(), (1, 1));
+        let board = Board::from_uci_position(&tree.uci_position()).unwrap();
+        assert_eq!(board.to_uci_moves(), "e2e4 c7c5 g1f3");
+    }
+
+    #[test]
+    fn sync_keeps_the_tree_of_the_same_start() {
+        let mut tree = MoveTree::default();
+        tree.sync(None, &["d2d4", "d7d5"]);
+        tree.sync(None, &["d2d4", "g8f6"]);
+        tree.back();
+        tree.forward();
+        assert_eq!(tree.path(), ["d2d4", "d7d5"]);
+
+        // another starting position starts a new tree
+        let fen = "4k3/8/8/8/8/8/8/4K3 w - - 0 1";
+        tree.sync(Some(fen), &["e1e2"]);
+        assert_eq!(tree.main_line(), ["e1e2"]);
+        assert_eq!(tree.uci_position(), format!("fen {} moves e1e2", fen));
+        tree.back();
+        assert!(!tree.back());
+        assert_eq!(tree.uci_position(), format!("fen {}", fen));
+    }
+}
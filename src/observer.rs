@@ -0,0 +1,22 @@
+use crate::{
+    board::Coords,
+    pieces::{PieceColor, PieceType},
+};
+
+/// Side of the board a king castled on
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CastlingSide {
+    Kingside,
+    Queenside,
+}
+
+/// Notified of what happens on the board, like a front-end playing sounds.
+/// Every notification does nothing by default
+pub trait GameObserver {
+    fn on_move(&mut self, _from: &Coords, _to: &Coords) {}
+    fn on_capture(&mut self, _piece: (PieceType, PieceColor)) {}
+    fn on_castle(&mut self, _side: CastlingSide) {}
+    fn on_promotion(&mut self, _piece: (PieceType, PieceColor)) {}
+    /// the king of this color is in check after the move
+    fn on_check(&mut self, _king_color: PieceColor) {}
+}
@@ -0,0 +1,66 @@
+use crate::board::Board;
+use std::{collections::HashMap, sync::OnceLock};
+
+/// Lines of the book in UCI notation from the initial position, with the opening they reach
+const BOOK_LINES: [(&str, &str); 22] = [
+    ("King's Pawn Opening", "e2e4"),
+    ("Queen's Pawn Opening", "d2d4"),
+    ("English Opening", "c2c4"),
+    ("Réti Opening", "g1f3"),
+    ("Open Game", "e2e4 e7e5"),
+    ("Sicilian Defense", "e2e4 c7c5"),
+    ("French Defense", "e2e4 e7e6"),
+    ("Caro-Kann Defense", "e2e4 c7c6"),
+    ("King's Knight Opening", "e2e4 e7e5 g1f3"),
+    ("Italian Game", "e2e4 e7e5 g1f3 b8c6 f1c4"),
+    ("Ruy Lopez", "e2e4 e7e5 g1f3 b8c6 f1b5"),
+    ("Scotch Game", "e2e4 e7e5 g1f3 b8c6 d2d4"),
+    ("Open Sicilian", "e2e4 c7c5 g1f3 d7d6 d2d4"),
+    (
+        "French Defense: Advance Variation",
+        "e2e4 e7e6 d2d4 d7d5 e4e5",
+    ),
+    (
+        "Caro-Kann Defense: Advance Variation",
+        "e2e4 c7c6 d2d4 d7d5 e4e5",
+    ),
+    ("Queen's Gambit", "d2d4 d7d5 c2c4"),
+    ("Queen's Gambit Declined", "d2d4 d7d5 c2c4 e7e6"),
+    ("Slav Defense", "d2d4 d7d5 c2c4 c7c6"),
+    ("Indian Defense", "d2d4 g8f6"),
+    ("King's Indian Defense", "d2d4 g8f6 c2c4 g7g6"),
+    ("Nimzo-Indian Defense", "d2d4 g8f6 c2c4 e7e6 b1c3 f8b4"),
+    ("English Opening: Reversed Sicilian", "c2c4 e7e5"),
+];
+
+/// What the book knows of a position
+#[derive(Debug, Default, PartialEq)]
+pub struct BookEntry {
+    /// opening reaching this position, `None` in the middle of a line
+    pub name: Option<&'static str>,
+    /// moves of the book from this position in UCI notation
+    pub moves: Vec<&'static str>,
+}
+
+/// Book entries keyed by `position_hash`, so a position reached by transposition finds its entry
+pub fn opening_book() -> &'static HashMap<u64, BookEntry> {
+    static BOOK: OnceLock<HashMap<u64, BookEntry>> = OnceLock::new();
+    BOOK.get_or_init(|| {
+        let mut book: HashMap<u64, BookEntry> = HashMap::new();
+        for (name, line) in BOOK_LINES {
+            let board = Board::from_uci_position(&format!("startpos moves {}", line))
+                .expect("the book lines are legal");
+            // the hash of each position is followed by the move played from it
+            for (hash, movement) in board.position_hashes.iter().zip(line.split_whitespace()) {
+                let entry = book.entry(*hash).or_default();
+                if !entry.moves.contains(&movement) {
+                    entry.moves.push(movement);
+                }
+            }
+            if let Some(hash) = board.position_hashes.last() {
+                book.entry(*hash).or_default().name = Some(name);
+            }
+        }
+        book
+    })
+}
@@ -1,10 +1,8 @@
 use super::{Movable, PieceColor, Position};
 use crate::{
     board::{Coords, GameBoard, HistRec},
-    utils::{
-        cleaned_positions, get_piece_color, impossible_positions_king_checked, is_cell_color_ally,
-        is_piece_opposite_king,
-    },
+    constants::DIAGONALS,
+    utils::{cleaned_positions, impossible_positions_king_checked, slide},
 };
 pub struct Bishop;
 
@@ -16,151 +14,14 @@ impl Movable for Bishop {
         allow_move_on_ally_positions: bool,
         _move_history: &[HistRec],
     ) -> Vec<Coords> {
-        let mut positions: Vec<Coords> = vec![];
-
-        let y = coordinates.row;
-        let x = coordinates.col;
-
-        // for diagonal from piece to top left
-        for i in 1..8i8 {
-            let new_x = x - i;
-            let new_y = y - i;
-            let new_coordinates = Coords::new(new_y, new_x);
-
-            // Invalid coords
-            if !&new_coordinates.is_valid() {
-                break;
-            }
-
-            // Empty cell
-            if get_piece_color(board, &new_coordinates).is_none() {
-                positions.push(new_coordinates);
-                continue;
-            }
-            // Ally cell
-            if is_cell_color_ally(board, new_coordinates.clone(), color) {
-                if !allow_move_on_ally_positions {
-                    break;
-                } else {
-                    positions.push(new_coordinates);
-                    break;
-                }
-            }
-
-            // Enemy cell
-            positions.push(new_coordinates.clone());
-            if !allow_move_on_ally_positions
-                || !is_piece_opposite_king(board[new_y as usize][new_x as usize], color)
-            {
-                break;
-            }
-        }
-
-        // for diagonal from piece to bottom right
-        for i in 1..8i8 {
-            let new_x = x + i;
-            let new_y = y + i;
-
-            let new_coordinates = Coords::new(new_y, new_x);
-
-            // Invalid coords
-            if !new_coordinates.is_valid() {
-                break;
-            }
-
-            // Empty cell
-            if get_piece_color(board, &new_coordinates).is_none() {
-                positions.push(new_coordinates);
-                continue;
-            }
-            // Ally cell
-            if is_cell_color_ally(board, new_coordinates.clone(), color) {
-                if !allow_move_on_ally_positions {
-                    break;
-                } else {
-                    positions.push(new_coordinates);
-                    break;
-                }
-            }
-
-            // Enemy cell
-            positions.push(new_coordinates.clone());
-            if !allow_move_on_ally_positions
-                || !is_piece_opposite_king(board[new_y as usize][new_x as usize], color)
-            {
-                break;
-            }
-        }
-
-        // for diagonal from piece to bottom left
-        for i in 1..8i8 {
-            let new_x = x - i;
-            let new_y = y + i;
-            let new_coordinates = Coords::new(new_y, new_x);
-
-            // Invalid coords
-            if !new_coordinates.is_valid() {
-                break;
-            }
-
-            // Empty cell
-            if get_piece_color(board, &new_coordinates).is_none() {
-                positions.push(new_coordinates);
-                continue;
-            }
-            // Ally cell
-            if is_cell_color_ally(board, new_coordinates.clone(), color) {
-                if !allow_move_on_ally_positions {
-                    break;
-                } else {
-                    positions.push(new_coordinates);
-                    break;
-                }
-            }
-
-            // Enemy cell
-            positions.push(new_coordinates);
-            if !allow_move_on_ally_positions
-                || !is_piece_opposite_king(board[new_y as usize][new_x as usize], color)
-            {
-                break;
-            }
-        }
-
-        // for diagonal from piece to top right
-        for i in 1..8i8 {
-            let new_x = x + i;
-            let new_y = y - i;
-            let new_coordinates = Coords::new(new_y, new_x);
-
-            // Invalid coords
-            if !new_coordinates.is_valid() {
-                break;
-            }
-
-            // Empty cell
-            if get_piece_color(board, &new_coordinates).is_none() {
-                positions.push(new_coordinates);
-                continue;
-            }
-            // Ally cell
-            if is_cell_color_ally(board, new_coordinates.clone(), color) {
-                if !allow_move_on_ally_positions {
-                    break;
-                } else {
-                    positions.push(new_coordinates);
-                    break;
-                }
-            }
+        let positions = slide(
+            coordinates,
+            color,
+            board,
+            &DIAGONALS,
+            allow_move_on_ally_positions,
+        );
 
-            // Enemy cell
-            positions.push(new_coordinates);
-            if !allow_move_on_ally_positions
-                || !is_piece_opposite_king(board[new_y as usize][new_x as usize], color)
-            {
-                break;
-            }
-        }
         cleaned_positions(positions)
     }
 }
@@ -171,7 +32,6 @@ impl Position for Bishop {
         color: PieceColor,
         board: GameBoard,
         move_history: &[HistRec],
-        _is_king_checked: bool,
     ) -> Vec<Coords> {
         // if the king is checked we clean all the position not resolving the check
         impossible_positions_king_checked(
@@ -209,7 +69,6 @@ mod tests {
     use crate::{
         board::{Board, Coords},
         pieces::{bishop::Bishop, PieceColor, PieceType, Position},
-        utils::is_getting_checked,
     };
 
     #[test]
@@ -253,13 +112,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Bishop::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Bishop::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -313,13 +167,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Bishop::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Bishop::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -387,13 +236,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Bishop::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Bishop::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -441,19 +285,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions = vec![Coords::new(4, 4)];
         right_positions.sort();
 
-        let mut positions = Bishop::authorized_positions(
-            &Coords::new(5, 5),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Bishop::authorized_positions(&Coords::new(5, 5), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -501,19 +337,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![];
         right_positions.sort();
 
-        let mut positions = Bishop::authorized_positions(
-            &Coords::new(5, 6),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Bishop::authorized_positions(&Coords::new(5, 6), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -561,19 +389,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![Coords::new(2, 6), Coords::new(3, 7)];
         right_positions.sort();
 
-        let mut positions = Bishop::authorized_positions(
-            &Coords::new(1, 5),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Bishop::authorized_positions(&Coords::new(1, 5), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -49,10 +49,11 @@ impl Position for King {
         color: PieceColor,
         board: GameBoard,
         move_history: &[HistRec],
-        is_king_checked: bool,
     ) -> Vec<Coords> {
         let mut positions: Vec<Coords> = vec![];
         let checked_cells = get_all_protected_cells(board, color, move_history);
+        // no castling out of a check
+        let is_king_checked = checked_cells.contains(coordinates);
 
         let rook_big_castle_x = 0;
         let rook_small_castle_x = 7;
@@ -60,28 +61,32 @@ impl Position for King {
         let king_line = if color == PieceColor::White { 7 } else { 0 };
 
         // We check the condition for small and big castling
-        if !did_piece_already_move(
-            move_history,
-            (PieceType::King, Coords::new(king_line, king_x)),
-        ) && !is_king_checked
+        if *coordinates == Coords::new(king_line, king_x)
+            && !did_piece_already_move(
+                move_history,
+                (PieceType::King, Coords::new(king_line, king_x)),
+            )
+            && !is_king_checked
         {
-            // We check if there is no pieces between tower and king
-
-            // Big castle check
+            // Big castle check, the king goes through d and c, b only has to be empty
             if !did_piece_already_move(
                 move_history,
                 (PieceType::Rook, Coords::new(king_line, rook_big_castle_x)),
-            ) && King::check_castling_condition(board, color, 0, 3, &checked_cells)
+            ) && King::check_castling_condition(board, color, rook_big_castle_x, &checked_cells)
             {
-                positions.push(Coords::new(king_line, 0));
+                positions.push(Coords::new(king_line, rook_big_castle_x));
             }
-            // Small castle check
+            // Small castle check, the king goes through f and g
             if !did_piece_already_move(
                 move_history,
                 (PieceType::Rook, Coords::new(king_line, rook_small_castle_x)),
-            ) && King::check_castling_condition(board, color, 5, 7, &checked_cells)
-            {
-                positions.push(Coords::new(king_line, 7));
+            ) && King::check_castling_condition(
+                board,
+                color,
+                rook_small_castle_x,
+                &checked_cells,
+            ) {
+                positions.push(Coords::new(king_line, rook_small_castle_x));
             }
         }
 
@@ -119,34 +124,31 @@ impl King {
     "
     }
 
-    // Check if nothing is in between the king and a rook and if none of those cells are getting checked
+    // Check if the rook is there, nothing is in between the king and the rook
+    // and none of the cells the king goes through are getting checked
     pub fn check_castling_condition(
         board: GameBoard,
         color: PieceColor,
-        start: i8,
-        end: i8,
+        rook_x: i8,
         checked_cells: &[Coords],
     ) -> bool {
         let king_line = if color == PieceColor::White { 7 } else { 0 };
-
-        let mut valid_for_castling = true;
-
-        for i in start..=end {
-            let new_coordinates = Coords::new(king_line, i);
-
-            if checked_cells.contains(&new_coordinates) {
-                valid_for_castling = false;
-            }
-            if (i == 7 || i == 0)
-                && (get_piece_type(board, &new_coordinates) != Some(PieceType::Rook)
-                    || !is_cell_color_ally(board, new_coordinates.clone(), color))
-                || (i != 7 && i != 0 && get_piece_type(board, &new_coordinates).is_some())
-            {
-                valid_for_castling = false;
-            }
-        }
-
-        valid_for_castling
+        let rook_coordinates = Coords::new(king_line, rook_x);
+        // cells between the king and the rook, and the two cells the king goes through
+        let (between, king_path) = if rook_x == 0 {
+            (1..=3, 2..=3)
+        } else {
+            (5..=6, 5..=6)
+        };
+
+        get_piece_type(board, &rook_coordinates) == Some(PieceType::Rook)
+            && is_cell_color_ally(board, rook_coordinates, color)
+            && between
+                .into_iter()
+                .all(|x| get_piece_type(board, &Coords::new(king_line, x)).is_none())
+            && king_path
+                .into_iter()
+                .all(|x| !checked_cells.contains(&Coords::new(king_line, x)))
     }
 }
 
@@ -155,7 +157,6 @@ mod tests {
     use crate::{
         board::{Board, Coords},
         pieces::{king::King, PieceColor, PieceType, Position},
-        utils::is_getting_checked,
     };
 
     #[test]
@@ -212,13 +213,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(4, 5), Coords::new(5, 4)];
         right_positions.sort();
 
-        let mut positions = King::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            King::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -278,13 +274,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(3, 4)];
         right_positions.sort();
 
-        let mut positions = King::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            King::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -344,13 +335,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(4, 5)];
         right_positions.sort();
 
-        let mut positions = King::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            King::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -410,13 +396,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(7, 3), Coords::new(7, 0)];
         right_positions.sort();
 
-        let mut positions = King::authorized_positions(
-            &Coords::new(7, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            King::authorized_positions(&Coords::new(7, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -476,13 +457,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(0, 5), Coords::new(0, 7)];
         right_positions.sort();
 
-        let mut positions = King::authorized_positions(
-            &Coords::new(0, 4),
-            PieceColor::Black,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            King::authorized_positions(&Coords::new(0, 4), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -542,13 +518,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(0, 5)];
         right_positions.sort();
 
-        let mut positions = King::authorized_positions(
-            &Coords::new(0, 4),
-            PieceColor::Black,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            King::authorized_positions(&Coords::new(0, 4), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -605,19 +576,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions = vec![Coords::new(0, 5)];
         right_positions.sort();
 
-        let mut positions = King::authorized_positions(
-            &Coords::new(0, 4),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            King::authorized_positions(&Coords::new(0, 4), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -680,16 +643,132 @@ mod tests {
         let mut positions = King::authorized_positions(
             &Coords::new(0, 4),
             PieceColor::Black,
-            board.board,
+            board.board(),
             &[
                 (PieceType::Rook, "0747".to_string()),
                 (PieceType::Pawn, "6252".to_string()),
                 (PieceType::Rook, "4707".to_string()),
             ],
-            false,
         );
         positions.sort();
 
         assert_eq!(right_positions, positions);
     }
+
+    #[test]
+    fn piece_move_corner() {
+        let board = Board::from_fen("8/8/8/8/8/8/8/K7 w - - 0 1").unwrap();
+
+        let mut positions =
+            King::authorized_positions(&Coords::new(7, 0), PieceColor::White, board.board(), &[]);
+        positions.sort();
+
+        let mut right_positions = vec![Coords::new(6, 0), Coords::new(6, 1), Coords::new(7, 1)];
+        right_positions.sort();
+        assert_eq!(right_positions, positions);
+    }
+
+    #[test]
+    fn cant_take_protected_piece() {
+        // the black pawn on b2 is protected by the black bishop
+        let board = Board::from_fen("8/8/8/8/3b4/8/1p6/K7 w - - 0 1").unwrap();
+        let positions =
+            King::authorized_positions(&Coords::new(7, 0), PieceColor::White, board.board(), &[]);
+
+        assert!(!positions.contains(&Coords::new(6, 1)));
+        assert!(board.board()[6][1].is_some_and(|piece| piece.0 == PieceType::Pawn));
+    }
+
+    fn white_king_positions(board: &Board) -> Vec<Coords> {
+        King::authorized_positions(
+            &Coords::new(7, 4),
+            PieceColor::White,
+            board.board(),
+            board.move_history(),
+        )
+    }
+
+    #[test]
+    fn small_castle_from_the_opening() {
+        let board =
+            Board::from_uci_position("startpos moves e2e4 e7e5 g1f3 b8c6 f1c4 g8f6").unwrap();
+
+        let positions = white_king_positions(&board);
+        assert!(positions.contains(&Coords::new(7, 7)));
+        assert!(!positions.contains(&Coords::new(7, 0)));
+    }
+
+    #[test]
+    fn castle_through_attacked_cell() {
+        // the black bishop on c4 attacks f1
+        let board = Board::from_fen("r3k2r/8/8/8/2b5/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        let positions = white_king_positions(&board);
+        assert!(!positions.contains(&Coords::new(7, 7)));
+        assert!(positions.contains(&Coords::new(7, 0)));
+    }
+
+    #[test]
+    fn big_castle_with_attacked_b_file() {
+        // b1 is attacked but the king doesn't go through it
+        let board = Board::from_fen("1r2k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1").unwrap();
+
+        let positions = white_king_positions(&board);
+        assert!(positions.contains(&Coords::new(7, 0)));
+        assert!(positions.contains(&Coords::new(7, 7)));
+    }
+
+    #[test]
+    fn no_castle_out_of_check() {
+        // the king finds the check of the rook on e8 by itself
+        let board = Board::from_fen("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+
+        let positions = white_king_positions(&board);
+        assert!(!positions.contains(&Coords::new(7, 0)));
+        assert!(!positions.contains(&Coords::new(7, 7)));
+        assert_eq!(positions.len(), 4);
+    }
+
+    #[test]
+    fn castle_blocked_by_piece() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/RN2K1NR w KQkq - 0 1").unwrap();
+
+        let positions = white_king_positions(&board);
+        assert!(!positions.contains(&Coords::new(7, 0)));
+        assert!(!positions.contains(&Coords::new(7, 7)));
+    }
+
+    #[test]
+    fn no_castle_away_from_home_cell() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R2K3R w - - 0 1").unwrap();
+
+        let positions = King::authorized_positions(
+            &Coords::new(7, 3),
+            PieceColor::White,
+            board.board(),
+            board.move_history(),
+        );
+        assert!(!positions.contains(&Coords::new(7, 0)));
+        assert!(!positions.contains(&Coords::new(7, 7)));
+    }
+
+    #[test]
+    fn kings_cant_be_adjacent() {
+        // the kings on e4 and e6 both cover e5 and the d5 and f5 cells
+        let board = Board::from_fen("8/8/4k3/8/4K3/8/8/8 w - - 0 1").unwrap();
+        let between = [Coords::new(3, 3), Coords::new(3, 4), Coords::new(3, 5)];
+
+        for (king, color) in [
+            (Coords::new(4, 4), PieceColor::White),
+            (Coords::new(2, 4), PieceColor::Black),
+        ] {
+            let positions =
+                King::authorized_positions(&king, color, board.board(), board.move_history());
+            assert_eq!(positions.len(), 5);
+            assert!(between.iter().all(|cell| !positions.contains(cell)));
+        }
+        assert!(!board
+            .legal_destinations(&Coords::new(4, 4))
+            .contains(&Coords::new(3, 4)));
+    }
 }
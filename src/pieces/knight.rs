@@ -1,7 +1,8 @@
 use super::{Movable, PieceColor, Position};
 use crate::{
     board::{Coords, GameBoard, HistRec},
-    utils::{cleaned_positions, impossible_positions_king_checked, is_cell_color_ally},
+    constants::KNIGHT_MOVES,
+    utils::{cleaned_positions, impossible_positions_king_checked, is_empty, is_enemy},
 };
 pub struct Knight;
 
@@ -15,34 +16,18 @@ impl Movable for Knight {
     ) -> Vec<Coords> {
         let mut positions: Vec<Coords> = Vec::new();
 
-        let (y, x) = (coordinates.row, coordinates.col);
-
         // Generate knight positions in all eight possible L-shaped moves
-        let piece_move = [
-            Coords::new(-2, -1),
-            Coords::new(-2, 1),
-            Coords::new(-1, -2),
-            Coords::new(-1, 2),
-            Coords::new(1, -2),
-            Coords::new(1, 2),
-            Coords::new(2, -1),
-            Coords::new(2, 1),
-        ];
-
-        for &Coords { col: dx, row: dy } in &piece_move {
-            let new_coordinates = Coords::new(y + dy, x + dx);
-
-            if !new_coordinates.is_valid() {
+        for (dy, dx) in KNIGHT_MOVES {
+            let Some(new_coordinates) = coordinates.offset(dy, dx) else {
                 continue;
-            }
+            };
 
-            if is_cell_color_ally(board, new_coordinates.clone(), color)
-                && !allow_move_on_ally_positions
+            if is_empty(board, &new_coordinates)
+                || is_enemy(board, &new_coordinates, color)
+                || allow_move_on_ally_positions
             {
-                continue;
+                positions.push(new_coordinates);
             }
-
-            positions.push(new_coordinates);
         }
 
         cleaned_positions(positions)
@@ -55,7 +40,6 @@ impl Position for Knight {
         color: PieceColor,
         board: GameBoard,
         move_history: &[HistRec],
-        _is_king_checked: bool,
     ) -> Vec<Coords> {
         impossible_positions_king_checked(
             coordinates,
@@ -93,7 +77,6 @@ mod tests {
     use crate::{
         board::{Board, Coords},
         pieces::{knight::Knight, PieceColor, PieceType, Position},
-        utils::is_getting_checked,
     };
 
     #[test]
@@ -132,13 +115,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Knight::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Knight::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -189,13 +167,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(6, 5)];
         right_positions.sort();
 
-        let mut positions = Knight::authorized_positions(
-            &Coords::new(7, 7),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Knight::authorized_positions(&Coords::new(7, 7), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -243,19 +216,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::White, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions = vec![Coords::new(7, 7)];
         right_positions.sort();
 
-        let mut positions = Knight::authorized_positions(
-            &Coords::new(6, 5),
-            PieceColor::White,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Knight::authorized_positions(&Coords::new(6, 5), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -303,19 +268,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::White, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![];
         right_positions.sort();
 
-        let mut positions = Knight::authorized_positions(
-            &Coords::new(6, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Knight::authorized_positions(&Coords::new(6, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -362,19 +319,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![];
         right_positions.sort();
 
-        let mut positions = Knight::authorized_positions(
-            &Coords::new(1, 4),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Knight::authorized_positions(&Coords::new(1, 4), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -1,4 +1,9 @@
-use crate::board::{Coords, GameBoard, HistRec};
+use std::{fmt, str::FromStr};
+
+use crate::{
+    board::{Coords, GameBoard, HistRec},
+    error::ChessError,
+};
 
 use self::{bishop::Bishop, king::King, knight::Knight, pawn::Pawn, queen::Queen, rook::Rook};
 
@@ -8,7 +13,7 @@ pub mod knight;
 pub mod pawn;
 pub mod queen;
 pub mod rook;
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum PieceType {
     Pawn,
     Rook,
@@ -25,39 +30,40 @@ impl PieceType {
         color: PieceColor,
         board: GameBoard,
         move_history: &[HistRec],
-        is_king_checked: bool,
     ) -> Vec<Coords> {
         match self {
-            PieceType::Pawn => {
-                Pawn::authorized_positions(coordinates, color, board, move_history, is_king_checked)
+            PieceType::Pawn => Pawn::authorized_positions(coordinates, color, board, move_history),
+            PieceType::Rook => Rook::authorized_positions(coordinates, color, board, move_history),
+            PieceType::Bishop => {
+                Bishop::authorized_positions(coordinates, color, board, move_history)
             }
-            PieceType::Rook => {
-                Rook::authorized_positions(coordinates, color, board, move_history, is_king_checked)
+            PieceType::Queen => {
+                Queen::authorized_positions(coordinates, color, board, move_history)
             }
-            PieceType::Bishop => Bishop::authorized_positions(
-                coordinates,
-                color,
-                board,
-                move_history,
-                is_king_checked,
-            ),
-            PieceType::Queen => Queen::authorized_positions(
-                coordinates,
-                color,
-                board,
-                move_history,
-                is_king_checked,
-            ),
-            PieceType::King => {
-                King::authorized_positions(coordinates, color, board, move_history, is_king_checked)
+            PieceType::King => King::authorized_positions(coordinates, color, board, move_history),
+            PieceType::Knight => {
+                Knight::authorized_positions(coordinates, color, board, move_history)
             }
-            PieceType::Knight => Knight::authorized_positions(
-                coordinates,
-                color,
-                board,
-                move_history,
-                is_king_checked,
-            ),
+        }
+    }
+
+    /// Cells the piece reaches by its own movement, whether it leaves its king in check or not
+    pub fn piece_move(
+        self,
+        coordinates: &Coords,
+        color: PieceColor,
+        board: GameBoard,
+        allow_move_on_ally_positions: bool,
+        move_history: &[HistRec],
+    ) -> Vec<Coords> {
+        let allow = allow_move_on_ally_positions;
+        match self {
+            PieceType::Pawn => Pawn::piece_move(coordinates, color, board, allow, move_history),
+            PieceType::Rook => Rook::piece_move(coordinates, color, board, allow, move_history),
+            PieceType::Bishop => Bishop::piece_move(coordinates, color, board, allow, move_history),
+            PieceType::Queen => Queen::piece_move(coordinates, color, board, allow, move_history),
+            PieceType::King => King::piece_move(coordinates, color, board, allow, move_history),
+            PieceType::Knight => Knight::piece_move(coordinates, color, board, allow, move_history),
         }
     }
 
@@ -152,6 +158,17 @@ impl PieceType {
         }
     }
 
+    /// Material value of the piece in pawns, the king has none
+    pub fn value(&self) -> i32 {
+        match self {
+            PieceType::Pawn => 1,
+            PieceType::Knight | PieceType::Bishop => 3,
+            PieceType::Rook => 5,
+            PieceType::Queen => 9,
+            PieceType::King => 0,
+        }
+    }
+
     pub fn piece_type_to_string_enum(piece_type: Option<PieceType>) -> &'static str {
         match piece_type {
             Some(PieceType::Queen) => Queen::to_string(),
@@ -165,7 +182,40 @@ impl PieceType {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+impl fmt::Display for PieceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PieceType::Pawn => "pawn",
+            PieceType::Rook => "rook",
+            PieceType::Bishop => "bishop",
+            PieceType::Queen => "queen",
+            PieceType::King => "king",
+            PieceType::Knight => "knight",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for PieceType {
+    type Err = ChessError;
+
+    /// Parses the name or the letter of a piece, case insensitive
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pawn" | "p" => Ok(PieceType::Pawn),
+            "rook" | "r" => Ok(PieceType::Rook),
+            "bishop" | "b" => Ok(PieceType::Bishop),
+            "queen" | "q" => Ok(PieceType::Queen),
+            "king" | "k" => Ok(PieceType::King),
+            "knight" | "n" => Ok(PieceType::Knight),
+            _ => Err(ChessError::InvalidNotation(format!(
+                "'{s}' is not a piece type"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum PieceColor {
     White = 0,
     Black = 1,
@@ -179,6 +229,29 @@ impl PieceColor {
     }
 }
 
+impl fmt::Display for PieceColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PieceColor::White => "white",
+            PieceColor::Black => "black",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for PieceColor {
+    type Err = ChessError;
+
+    /// Parses the name or the FEN letter of a color, case insensitive
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "white" | "w" => Ok(PieceColor::White),
+            "black" | "b" => Ok(PieceColor::Black),
+            _ => Err(ChessError::InvalidNotation(format!("'{s}' is not a color"))),
+        }
+    }
+}
+
 pub trait Movable {
     fn piece_move(
         coordinates: &Coords,
@@ -190,12 +263,13 @@ pub trait Movable {
 }
 
 pub trait Position {
+    /// Cells the piece can move to without leaving its king in check. Each piece finds
+    /// whether its king is checked when it needs to, the king to refuse castling out of a check
     fn authorized_positions(
         coordinates: &Coords,
         color: PieceColor,
         board: GameBoard,
         move_history: &[HistRec],
-        is_king_checked: bool,
     ) -> Vec<Coords>;
 
     fn protected_positions(
@@ -205,3 +279,58 @@ pub trait Position {
         move_history: &[HistRec],
     ) -> Vec<Coords>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PieceColor, PieceType};
+
+    #[test]
+    fn parse_piece_type() {
+        assert_eq!("knight".parse::<PieceType>().unwrap(), PieceType::Knight);
+        assert_eq!("n".parse::<PieceType>().unwrap(), PieceType::Knight);
+        assert_eq!("Q".parse::<PieceType>().unwrap(), PieceType::Queen);
+        assert_eq!("Bishop".parse::<PieceType>().unwrap(), PieceType::Bishop);
+        assert!("horse".parse::<PieceType>().is_err());
+        assert!("".parse::<PieceType>().is_err());
+    }
+
+    #[test]
+    fn parse_piece_color() {
+        assert_eq!("white".parse::<PieceColor>().unwrap(), PieceColor::White);
+        assert_eq!("w".parse::<PieceColor>().unwrap(), PieceColor::White);
+        assert_eq!("Black".parse::<PieceColor>().unwrap(), PieceColor::Black);
+        assert_eq!("b".parse::<PieceColor>().unwrap(), PieceColor::Black);
+        assert!("red".parse::<PieceColor>().is_err());
+    }
+
+    #[test]
+    fn piece_values() {
+        assert_eq!(PieceType::Pawn.value(), 1);
+        assert_eq!(PieceType::Knight.value(), PieceType::Bishop.value());
+        assert_eq!(PieceType::Rook.value(), 5);
+        assert_eq!(PieceType::Queen.value(), 9);
+        assert_eq!(PieceType::King.value(), 0);
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for piece_type in [
+            PieceType::Pawn,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Queen,
+            PieceType::King,
+            PieceType::Knight,
+        ] {
+            assert_eq!(
+                piece_type.to_string().parse::<PieceType>().unwrap(),
+                piece_type
+            );
+        }
+        for color in [PieceColor::White, PieceColor::Black] {
+            assert_eq!(color.to_string().parse::<PieceColor>().unwrap(), color);
+        }
+        assert_eq!(PieceType::Knight.to_string(), "knight");
+        assert_eq!(PieceColor::Black.to_string(), "black");
+    }
+}
@@ -2,8 +2,8 @@ use super::{Movable, PieceColor, PieceType, Position};
 use crate::{
     board::{Coords, GameBoard, HistRec},
     utils::{
-        chtoi, cleaned_positions, get_latest_move, get_piece_color,
-        impossible_positions_king_checked, is_cell_color_ally,
+        cleaned_positions, get_latest_move, get_piece_color, impossible_positions_king_checked,
+        is_cell_color_ally, parse_move_str,
     },
 };
 
@@ -89,11 +89,12 @@ impl Movable for Pawn {
         // We check for en passant
         let latest_move = get_latest_move(move_history);
 
-        if let Some((PieceType::Pawn, piece_move)) = latest_move {
-            let from_y = chtoi(piece_move.chars().nth(0));
-            let from_x = chtoi(piece_move.chars().nth(1));
-            let to_y = chtoi(piece_move.chars().nth(2));
-            let to_x = chtoi(piece_move.chars().nth(3));
+        if let Some((from, to)) = latest_move
+            .filter(|(piece_type, _)| *piece_type == PieceType::Pawn)
+            .and_then(|(_, piece_move)| parse_move_str(&piece_move))
+        {
+            let (from_y, from_x) = (from.row, from.col);
+            let (to_y, to_x) = (to.row, to.col);
 
             let valid_y_start: i8;
             let number_of_cells_move: i8;
@@ -130,7 +131,6 @@ impl Position for Pawn {
         color: PieceColor,
         board: GameBoard,
         move_history: &[HistRec],
-        _is_king_checked: bool,
     ) -> Vec<Coords> {
         // If the king is not checked we get then normal moves
         // if the king is checked we clean all the position not resolving the check
@@ -154,6 +154,28 @@ impl Position for Pawn {
 }
 
 impl Pawn {
+    /// Whether a pawn of `color` reaching `to` gets promoted
+    pub fn is_promotion_move(color: PieceColor, to: &Coords) -> bool {
+        let last_row = if color == PieceColor::White { 0 } else { 7 };
+        to.row == last_row
+    }
+
+    /// Authorized positions of the pawn, flagged with whether they promote it
+    pub fn authorized_positions_with_promotion(
+        coordinates: &Coords,
+        color: PieceColor,
+        board: GameBoard,
+        move_history: &[HistRec],
+    ) -> Vec<(Coords, bool)> {
+        Self::authorized_positions(coordinates, color, board, move_history)
+            .into_iter()
+            .map(|position| {
+                let is_promotion = Self::is_promotion_move(color, &position);
+                (position, is_promotion)
+            })
+            .collect()
+    }
+
     pub fn to_string() -> &'static str {
         "\
         \n\
@@ -170,7 +192,6 @@ mod tests {
     use crate::{
         board::{Board, Coords},
         pieces::{pawn::Pawn, PieceColor, PieceType, Position},
-        utils::is_getting_checked,
     };
 
     #[test]
@@ -200,13 +221,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(3, 4)];
         right_positions.sort();
 
-        let mut positions = Pawn::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Pawn::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
         assert_eq!(right_positions, positions);
     }
@@ -238,13 +254,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(5, 4), Coords::new(4, 4)];
         right_positions.sort();
 
-        let mut positions = Pawn::authorized_positions(
-            &Coords::new(6, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Pawn::authorized_positions(&Coords::new(6, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
         assert_eq!(right_positions, positions);
     }
@@ -290,13 +301,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Pawn::authorized_positions(
-            &Coords::new(1, 3),
-            PieceColor::Black,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Pawn::authorized_positions(&Coords::new(1, 3), PieceColor::Black, board.board(), &[]);
         positions.sort();
         assert_eq!(right_positions, positions);
     }
@@ -337,13 +343,8 @@ mod tests {
         let mut right_positions = vec![Coords::new(2, 4), Coords::new(2, 2)];
         right_positions.sort();
 
-        let mut positions = Pawn::authorized_positions(
-            &Coords::new(1, 3),
-            PieceColor::Black,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Pawn::authorized_positions(&Coords::new(1, 3), PieceColor::Black, board.board(), &[]);
         positions.sort();
         assert_eq!(right_positions, positions);
     }
@@ -378,9 +379,8 @@ mod tests {
         let mut positions = Pawn::authorized_positions(
             &Coords::new(3, 3),
             PieceColor::White,
-            board.board,
+            board.board(),
             &[(PieceType::Pawn, "1232".to_string())],
-            false,
         );
         positions.sort();
         assert_eq!(right_positions, positions);
@@ -416,9 +416,8 @@ mod tests {
         let mut positions = Pawn::authorized_positions(
             &Coords::new(4, 2),
             PieceColor::Black,
-            board.board,
+            board.board(),
             &[(PieceType::Pawn, "6343".to_string())],
-            false,
         );
         positions.sort();
         assert_eq!(right_positions, positions);
@@ -463,9 +462,8 @@ mod tests {
         let mut positions = Pawn::authorized_positions(
             &Coords::new(1, 1),
             PieceColor::Black,
-            board.board,
+            board.board(),
             &[(PieceType::Pawn, "6343".to_string())],
-            false,
         );
         positions.sort();
         assert_eq!(right_positions, positions);
@@ -504,19 +502,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions = vec![Coords::new(3, 2)];
         right_positions.sort();
 
-        let mut positions = Pawn::authorized_positions(
-            &Coords::new(2, 3),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Pawn::authorized_positions(&Coords::new(2, 3), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -555,19 +545,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![];
         right_positions.sort();
 
-        let mut positions = Pawn::authorized_positions(
-            &Coords::new(2, 4),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Pawn::authorized_positions(&Coords::new(2, 4), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -615,21 +597,120 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![];
         right_positions.sort();
 
-        let mut positions = Pawn::authorized_positions(
-            &Coords::new(1, 5),
-            PieceColor::Black,
-            board.board,
+        let mut positions =
+            Pawn::authorized_positions(&Coords::new(1, 5), PieceColor::Black, board.board(), &[]);
+        positions.sort();
+
+        assert_eq!(right_positions, positions);
+    }
+
+    #[test]
+    fn promotion_flagged_positions() {
+        let custom_board = [
+            [
+                None,
+                None,
+                None,
+                Some((PieceType::Rook, PieceColor::Black)),
+                None,
+                None,
+                None,
+                Some((PieceType::King, PieceColor::Black)),
+            ],
+            [
+                None,
+                None,
+                None,
+                None,
+                Some((PieceType::Pawn, PieceColor::White)),
+                None,
+                None,
+                None,
+            ],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+            [
+                None,
+                None,
+                None,
+                None,
+                Some((PieceType::Pawn, PieceColor::White)),
+                None,
+                None,
+                None,
+            ],
+            [
+                None,
+                None,
+                None,
+                None,
+                Some((PieceType::King, PieceColor::White)),
+                None,
+                None,
+                None,
+            ],
+        ];
+        let board = Board::new(custom_board, PieceColor::White, vec![]);
+
+        // The pawn on the 7th rank promotes by moving forward or by taking the rook
+        let mut positions = Pawn::authorized_positions_with_promotion(
+            &Coords::new(1, 4),
+            PieceColor::White,
+            board.board(),
             &[],
-            is_king_checked,
         );
         positions.sort();
+        assert_eq!(
+            positions,
+            vec![(Coords::new(0, 3), true), (Coords::new(0, 4), true)]
+        );
 
+        // The pawn on its starting row doesn't
+        let mut positions = Pawn::authorized_positions_with_promotion(
+            &Coords::new(6, 4),
+            PieceColor::White,
+            board.board(),
+            &[],
+        );
+        positions.sort();
+        assert_eq!(
+            positions,
+            vec![(Coords::new(4, 4), false), (Coords::new(5, 4), false)]
+        );
+
+        assert!(board.is_promotion_move(&Coords::new(1, 4), &Coords::new(0, 4)));
+        assert!(!board.is_promotion_move(&Coords::new(6, 4), &Coords::new(5, 4)));
+    }
+
+    #[test]
+    fn double_push_blocked() {
+        let board = Board::from_fen("8/8/8/8/4n3/8/4P3/8 w - - 0 1").unwrap();
+        let positions =
+            Pawn::authorized_positions(&Coords::new(6, 4), PieceColor::White, board.board(), &[]);
+        assert_eq!(positions, vec![Coords::new(5, 4)]);
+
+        let board = Board::from_fen("8/8/8/8/8/4n3/4P3/8 w - - 0 1").unwrap();
+        let positions =
+            Pawn::authorized_positions(&Coords::new(6, 4), PieceColor::White, board.board(), &[]);
+        assert!(positions.is_empty());
+    }
+
+    #[test]
+    fn capture_on_edge_file() {
+        let board = Board::from_fen("8/p7/1N6/8/8/8/8/8 b - - 0 1").unwrap();
+
+        let mut positions =
+            Pawn::authorized_positions(&Coords::new(1, 0), PieceColor::Black, board.board(), &[]);
+        positions.sort();
+
+        let mut right_positions = vec![Coords::new(2, 0), Coords::new(3, 0), Coords::new(2, 1)];
+        right_positions.sort();
         assert_eq!(right_positions, positions);
+        assert!(board.board()[2][1].is_some_and(|piece| piece.0 == PieceType::Knight));
     }
 }
@@ -1,8 +1,7 @@
-use super::rook::Rook;
 use super::{Movable, PieceColor, Position};
 use crate::board::{Coords, GameBoard, HistRec};
-use crate::pieces::bishop::Bishop;
-use crate::utils::{cleaned_positions, impossible_positions_king_checked};
+use crate::constants::{DIAGONALS, ORTHOGONALS};
+use crate::utils::{cleaned_positions, impossible_positions_king_checked, slide};
 
 pub struct Queen;
 
@@ -12,25 +11,15 @@ impl Movable for Queen {
         color: PieceColor,
         board: GameBoard,
         allow_move_on_ally_positions: bool,
-        move_history: &[HistRec],
+        _move_history: &[HistRec],
     ) -> Vec<Coords> {
-        let mut positions: Vec<Coords> = vec![];
-
-        // Queen = bishop concat rook
-        positions.extend(Bishop::piece_move(
+        let positions = slide(
             coordinates,
             color,
             board,
+            &[DIAGONALS, ORTHOGONALS].concat(),
             allow_move_on_ally_positions,
-            move_history,
-        ));
-        positions.extend(Rook::piece_move(
-            coordinates,
-            color,
-            board,
-            allow_move_on_ally_positions,
-            move_history,
-        ));
+        );
 
         cleaned_positions(positions)
     }
@@ -42,7 +31,6 @@ impl Position for Queen {
         color: PieceColor,
         board: GameBoard,
         move_history: &[HistRec],
-        _is_king_checked: bool,
     ) -> Vec<Coords> {
         impossible_positions_king_checked(
             coordinates,
@@ -78,8 +66,7 @@ impl Queen {
 mod tests {
     use crate::{
         board::{Board, Coords},
-        pieces::{queen::Queen, PieceColor, PieceType, Position},
-        utils::is_getting_checked,
+        pieces::{queen::Queen, Movable, PieceColor, PieceType, Position},
     };
 
     #[test]
@@ -137,13 +124,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Queen::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Queen::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -211,13 +193,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Queen::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Queen::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -295,13 +272,8 @@ mod tests {
 
         right_positions.sort();
 
-        let mut positions = Queen::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Queen::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -348,19 +320,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions = vec![Coords::new(4, 4)];
         right_positions.sort();
 
-        let mut positions = Queen::authorized_positions(
-            &Coords::new(5, 5),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Queen::authorized_positions(&Coords::new(5, 5), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -407,19 +371,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![];
         right_positions.sort();
 
-        let mut positions = Queen::authorized_positions(
-            &Coords::new(5, 6),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Queen::authorized_positions(&Coords::new(5, 6), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -467,21 +423,126 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![Coords::new(2, 6), Coords::new(3, 7)];
         right_positions.sort();
 
-        let mut positions = Queen::authorized_positions(
-            &Coords::new(1, 5),
-            PieceColor::Black,
-            board.board,
+        let mut positions =
+            Queen::authorized_positions(&Coords::new(1, 5), PieceColor::Black, board.board(), &[]);
+        positions.sort();
+
+        assert_eq!(right_positions, positions);
+    }
+
+    #[test]
+    fn piece_move_open_board() {
+        let board = Board::from_fen("8/8/8/8/3Q4/8/8/8 w - - 0 1").unwrap();
+
+        let mut positions = Queen::piece_move(
+            &Coords::new(4, 3),
+            PieceColor::White,
+            board.board(),
+            false,
             &[],
-            is_king_checked,
         );
         positions.sort();
 
+        // the whole 4th rank and d file, and both diagonals
+        let mut right_positions = vec![
+            Coords::new(4, 0),
+            Coords::new(4, 1),
+            Coords::new(4, 2),
+            Coords::new(4, 4),
+            Coords::new(4, 5),
+            Coords::new(4, 6),
+            Coords::new(4, 7),
+            Coords::new(0, 3),
+            Coords::new(1, 3),
+            Coords::new(2, 3),
+            Coords::new(3, 3),
+            Coords::new(5, 3),
+            Coords::new(6, 3),
+            Coords::new(7, 3),
+            Coords::new(3, 4),
+            Coords::new(2, 5),
+            Coords::new(1, 6),
+            Coords::new(0, 7),
+            Coords::new(5, 2),
+            Coords::new(6, 1),
+            Coords::new(7, 0),
+            Coords::new(3, 2),
+            Coords::new(2, 1),
+            Coords::new(1, 0),
+            Coords::new(5, 4),
+            Coords::new(6, 5),
+            Coords::new(7, 6),
+        ];
+        right_positions.sort();
+
+        assert_eq!(right_positions, positions);
+    }
+
+    #[test]
+    fn piece_move_blocked_board() {
+        // allies on d5, c4 and c3, enemies on f6 and h4
+        let board = Board::from_fen("8/8/5n2/3P4/2PQ3r/2P5/8/8 w - - 0 1").unwrap();
+
+        let mut positions = Queen::piece_move(
+            &Coords::new(4, 3),
+            PieceColor::White,
+            board.board(),
+            false,
+            &[],
+        );
+        positions.sort();
+
+        let mut right_positions = vec![
+            // down the d file
+            Coords::new(5, 3),
+            Coords::new(6, 3),
+            Coords::new(7, 3),
+            // right up to the rook
+            Coords::new(4, 4),
+            Coords::new(4, 5),
+            Coords::new(4, 6),
+            Coords::new(4, 7),
+            // up to the knight
+            Coords::new(3, 4),
+            Coords::new(2, 5),
+            Coords::new(3, 2),
+            Coords::new(2, 1),
+            Coords::new(1, 0),
+            Coords::new(5, 4),
+            Coords::new(6, 5),
+            Coords::new(7, 6),
+        ];
+        right_positions.sort();
+
         assert_eq!(right_positions, positions);
+
+        // the allies are protected
+        let protected = Queen::piece_move(
+            &Coords::new(4, 3),
+            PieceColor::White,
+            board.board(),
+            true,
+            &[],
+        );
+        assert!(protected.contains(&Coords::new(3, 3)));
+        assert!(protected.contains(&Coords::new(4, 2)));
+        assert!(protected.contains(&Coords::new(5, 2)));
+        assert_eq!(protected.len(), right_positions.len() + 3);
+    }
+
+    #[test]
+    fn piece_move_corner() {
+        let board = Board::from_fen("7Q/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+
+        let positions =
+            Queen::authorized_positions(&Coords::new(0, 7), PieceColor::White, board.board(), &[]);
+
+        assert_eq!(positions.len(), 21);
+        assert!(positions.contains(&Coords::new(7, 0)));
+        assert!(positions.contains(&Coords::new(0, 0)));
+        assert!(positions.contains(&Coords::new(7, 7)));
     }
 }
@@ -1,10 +1,8 @@
 use super::{Movable, PieceColor, Position};
 use crate::{
     board::{Coords, GameBoard, HistRec},
-    utils::{
-        cleaned_positions, get_piece_color, impossible_positions_king_checked, is_cell_color_ally,
-        is_piece_opposite_king,
-    },
+    constants::ORTHOGONALS,
+    utils::{cleaned_positions, impossible_positions_king_checked, slide},
 };
 
 pub struct Rook;
@@ -18,151 +16,13 @@ impl Movable for Rook {
         _move_history: &[HistRec],
     ) -> Vec<Coords> {
         // Pawns can only move in one direction depending on their color
-        let mut positions: Vec<Coords> = vec![];
-
-        let (y, x) = (coordinates.row, coordinates.col);
-
-        // RIGHT ROW
-        for i in 1..8i8 {
-            let new_x = x + i;
-            let new_y = y;
-            let new_coordinates = Coords::new(new_y, new_x);
-
-            // Invalid coords
-            if !new_coordinates.is_valid() {
-                break;
-            }
-
-            // Empty cell
-            if get_piece_color(board, &new_coordinates).is_none() {
-                positions.push(new_coordinates);
-                continue;
-            }
-            // Ally cell
-            if is_cell_color_ally(board, new_coordinates.clone(), color) {
-                if !allow_move_on_ally_positions {
-                    break;
-                } else {
-                    positions.push(new_coordinates.clone());
-                    break;
-                }
-            }
-
-            // Enemy cell
-            positions.push(new_coordinates);
-            if !allow_move_on_ally_positions
-                || !is_piece_opposite_king(board[new_y as usize][new_x as usize], color)
-            {
-                break;
-            }
-        }
-
-        // LEFT ROW
-        for i in 1..=8 {
-            let new_x = x - i;
-            let new_y = y;
-            let new_coordinates = Coords::new(new_y, new_x);
-
-            // Invalid coords
-            if !new_coordinates.is_valid() {
-                break;
-            }
-
-            // Empty piece
-            if get_piece_color(board, &new_coordinates).is_none() {
-                positions.push(new_coordinates);
-                continue;
-            }
-
-            // Ally piece
-            if is_cell_color_ally(board, new_coordinates.clone(), color) {
-                if !allow_move_on_ally_positions {
-                    break;
-                } else {
-                    positions.push(new_coordinates);
-                    break;
-                }
-            }
-
-            // Enemy cell
-            positions.push(new_coordinates);
-            if !allow_move_on_ally_positions
-                || !is_piece_opposite_king(board[new_y as usize][new_x as usize], color)
-            {
-                break;
-            }
-        }
-
-        // BOTTOM ROW
-        for i in 1..8i8 {
-            let new_x = x;
-            let new_y = y + i;
-            let new_coordinates = Coords::new(new_y, new_x);
-
-            // Invalid coords
-            if !new_coordinates.is_valid() {
-                break;
-            }
-
-            // Empty cell
-            if get_piece_color(board, &new_coordinates).is_none() {
-                positions.push(new_coordinates);
-                continue;
-            }
-            // Ally cell
-            if is_cell_color_ally(board, new_coordinates.clone(), color) {
-                if !allow_move_on_ally_positions {
-                    break;
-                } else {
-                    positions.push(new_coordinates);
-                    break;
-                }
-            }
-
-            // Enemy cell
-            positions.push(new_coordinates);
-
-            if !allow_move_on_ally_positions
-                || !is_piece_opposite_king(board[new_y as usize][new_x as usize], color)
-            {
-                break;
-            }
-        }
-
-        // UP ROW
-        for i in 1..8i8 {
-            let new_x = x;
-            let new_y = y - i;
-            let new_coordinates = Coords::new(new_y, new_x);
-
-            // Invalid coords
-            if !new_coordinates.is_valid() {
-                break;
-            }
-
-            // Empty cell
-            if get_piece_color(board, &new_coordinates).is_none() {
-                positions.push(new_coordinates);
-                continue;
-            }
-            // Ally cell
-            if is_cell_color_ally(board, new_coordinates.clone(), color) {
-                if !allow_move_on_ally_positions {
-                    break;
-                } else {
-                    positions.push(new_coordinates);
-                    break;
-                }
-            }
-            // Enemy cell
-            positions.push(new_coordinates);
-
-            if !allow_move_on_ally_positions
-                || !is_piece_opposite_king(board[new_y as usize][new_x as usize], color)
-            {
-                break;
-            }
-        }
+        let positions = slide(
+            coordinates,
+            color,
+            board,
+            &ORTHOGONALS,
+            allow_move_on_ally_positions,
+        );
 
         cleaned_positions(positions)
     }
@@ -174,7 +34,6 @@ impl Position for Rook {
         color: PieceColor,
         board: GameBoard,
         move_history: &[HistRec],
-        _is_king_checked: bool,
     ) -> Vec<Coords> {
         // If the king is not checked we get then normal moves
         // if the king is checked we clean all the position not resolving the check
@@ -214,7 +73,6 @@ mod tests {
     use crate::{
         board::{Board, Coords},
         pieces::{rook::Rook, PieceColor, PieceType, Position},
-        utils::is_getting_checked,
     };
 
     #[test]
@@ -259,13 +117,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Rook::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Rook::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
         assert_eq!(right_positions, positions);
     }
@@ -318,13 +171,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Rook::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Rook::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
         assert_eq!(right_positions, positions);
     }
@@ -383,13 +231,8 @@ mod tests {
         ];
         right_positions.sort();
 
-        let mut positions = Rook::authorized_positions(
-            &Coords::new(4, 4),
-            PieceColor::White,
-            board.board,
-            &[],
-            false,
-        );
+        let mut positions =
+            Rook::authorized_positions(&Coords::new(4, 4), PieceColor::White, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -437,19 +280,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions = vec![Coords::new(4, 2)];
         right_positions.sort();
 
-        let mut positions = Rook::authorized_positions(
-            &Coords::new(5, 2),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Rook::authorized_positions(&Coords::new(5, 2), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -497,19 +332,11 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![];
         right_positions.sort();
 
-        let mut positions = Rook::authorized_positions(
-            &Coords::new(5, 3),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Rook::authorized_positions(&Coords::new(5, 3), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
@@ -556,21 +383,38 @@ mod tests {
         let mut board = Board::new(custom_board, PieceColor::Black, vec![]);
         board.set_board(custom_board);
 
-        let is_king_checked =
-            is_getting_checked(board.board, board.player_turn, &board.move_history);
-
         let mut right_positions: Vec<Coords> = vec![Coords::new(2, 4), Coords::new(3, 4)];
         right_positions.sort();
 
-        let mut positions = Rook::authorized_positions(
-            &Coords::new(1, 4),
-            PieceColor::Black,
-            board.board,
-            &[],
-            is_king_checked,
-        );
+        let mut positions =
+            Rook::authorized_positions(&Coords::new(1, 4), PieceColor::Black, board.board(), &[]);
         positions.sort();
 
         assert_eq!(right_positions, positions);
     }
+
+    #[test]
+    fn piece_move_corner() {
+        let board = Board::from_fen("8/8/8/8/8/8/8/R7 w - - 0 1").unwrap();
+
+        let positions =
+            Rook::authorized_positions(&Coords::new(7, 0), PieceColor::White, board.board(), &[]);
+
+        assert_eq!(positions.len(), 14);
+        assert!(positions.contains(&Coords::new(0, 0)));
+        assert!(positions.contains(&Coords::new(7, 7)));
+    }
+
+    #[test]
+    fn nailing_along_the_line() {
+        // the rook is pinned on the e file, it can still move along it
+        let board = Board::from_fen("4r3/8/8/8/8/8/4R3/4K3 w - - 0 1").unwrap();
+        let mut positions =
+            Rook::authorized_positions(&Coords::new(6, 4), PieceColor::White, board.board(), &[]);
+        positions.sort();
+
+        let mut right_positions: Vec<Coords> = (0..6).map(|row| Coords::new(row, 4)).collect();
+        right_positions.sort();
+        assert_eq!(right_positions, positions);
+    }
 }
@@ -1,9 +1,4 @@
-use crate::{
-    app::App,
-    constants::WHITE,
-    pieces::{bishop::Bishop, knight::Knight, queen::Queen, rook::Rook},
-    ui::centered_rect,
-};
+use crate::{app::App, constants::WHITE, ui::centered_rect};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
     style::{Color, Style, Stylize},
@@ -41,6 +36,67 @@ pub fn render_engine_path_error_popup(frame: &mut Frame) {
     frame.render_widget(paragraph, area);
 }
 
+// This renders a popup when the chess engine stopped during a game
+pub fn render_engine_error_popup(frame: &mut Frame, error: &str) {
+    let block = Block::default()
+        .title("Error")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(WHITE));
+    let area = centered_rect(40, 40, frame.size());
+
+    let text = vec![
+        Line::from("The chess engine stopped").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(error.to_string()),
+        Line::from(""),
+        Line::from("Press 'S' to start the engine again"),
+        Line::from("Press 'P' to keep playing both sides locally"),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(block.clone())
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area); //this clears out the background
+    frame.render_widget(block, area);
+    frame.render_widget(paragraph, area);
+}
+
+// This renders a popup to type or paste a fen position
+pub fn render_fen_input_popup(frame: &mut Frame, fen: &str, error: Option<&str>) {
+    let block = Block::default()
+        .title("Load a FEN position")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(WHITE));
+    let area = centered_rect(60, 30, frame.size());
+
+    let mut text = vec![
+        Line::from("Type or paste a FEN position").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(format!("> {}", fen)),
+        Line::from(""),
+    ];
+    if let Some(error) = error {
+        text.push(Line::from(error.to_string().red()));
+        text.push(Line::from(""));
+    }
+    text.push(Line::from("Press `Enter` to load it, `Esc` to cancel").alignment(Alignment::Center));
+
+    let paragraph = Paragraph::new(text)
+        .block(block.clone())
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area); //this clears out the background
+    frame.render_widget(block, area);
+    frame.render_widget(paragraph, area);
+}
+
 // This renders a popup for a promotion
 pub fn render_end_popup(frame: &mut Frame, sentence: String) {
     let block = Block::default()
@@ -72,7 +128,7 @@ pub fn render_promotion_popup(frame: &mut Frame, app: &App) {
     let block = Block::default()
         .title("Pawn promotion")
         .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
+        .border_type(app.board.theme.border_type())
         .padding(Padding::horizontal(1))
         .border_style(Style::default().fg(WHITE));
     let area = centered_rect(40, 40, frame.size());
@@ -116,42 +172,38 @@ pub fn render_promotion_popup(frame: &mut Frame, app: &App) {
         )
         .split(inner_popup_layout_vertical[1]);
 
-    let queen_p = Paragraph::new(Queen::to_string())
-        .block(Block::default())
-        .alignment(Alignment::Center)
-        .style(Style::default().bg(if app.board.promotion_cursor == 0 {
-            Color::LightBlue
-        } else {
-            Color::Reset // Set to the default background color when the condition is false
-        }));
-    frame.render_widget(queen_p, inner_popup_layout_horizontal[0]);
-    let rook_p = Paragraph::new(Rook::to_string())
-        .block(Block::default())
-        .alignment(Alignment::Center)
-        .style(Style::default().bg(if app.board.promotion_cursor == 1 {
-            Color::LightBlue
-        } else {
-            Color::Reset // Set to the default background color when the condition is false
-        }));
-    frame.render_widget(rook_p, inner_popup_layout_horizontal[1]);
-    let bishop_p = Paragraph::new(Bishop::to_string())
-        .block(Block::default())
-        .alignment(Alignment::Center)
-        .style(Style::default().bg(if app.board.promotion_cursor == 2 {
-            Color::LightBlue
-        } else {
-            Color::Reset // Set to the default background color when the condition is false
-        }));
-    frame.render_widget(bishop_p, inner_popup_layout_horizontal[2]);
-    let knight_p = Paragraph::new(Knight::to_string())
-        .block(Block::default())
-        .alignment(Alignment::Center)
-        .style(Style::default().bg(if app.board.promotion_cursor == 3 {
-            Color::LightBlue
-        } else {
-            Color::Reset // Set to the default background color when the condition is false
-        }));
-    frame.render_widget(knight_p, inner_popup_layout_horizontal[3]);
+    for (promotion_cursor, area) in inner_popup_layout_horizontal.iter().enumerate() {
+        let Some((piece_type, piece_color)) = app.board.promotion_choice(promotion_cursor as i8)
+        else {
+            continue;
+        };
+        let text = vec![
+            Line::from(app.board.theme.piece_glyph(piece_type, piece_color)),
+            Line::from(""),
+        ]
+        .into_iter()
+        .chain(
+            app.board
+                .theme
+                .piece_art(Some(piece_type), Some(piece_color))
+                .lines()
+                .map(Line::from),
+        )
+        .collect::<Vec<Line>>();
+
+        // the glyphs tell the colors apart, the default foreground stays readable on any terminal theme
+        let piece_p = Paragraph::new(text)
+            .block(Block::default())
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Reset).bg(
+                if app.board.promotion_cursor == promotion_cursor as i8 {
+                    Color::LightBlue
+                } else {
+                    Color::Reset // Set to the default background color when the condition is false
+                },
+            ));
+        frame.render_widget(piece_p, *area);
+    }
 }
 
 // This render the credit popup
@@ -214,9 +266,19 @@ pub fn render_help_popup(frame: &mut Frame) {
         Line::from("`Ctrl` '+' or '-': Zoom in or out to adjust pieces sizes"),
         Line::from("(Might differ in certain terminals)"),
         Line::from(""),
-        Line::from("`Space`: Select a piece"),
+        Line::from("`Space`: Select a piece, confirm a move with `--confirm-moves`"),
+        Line::from(""),
+        Line::from("`Esc`: Deselect a piece / cancel a move to confirm / hide popups"),
+        Line::from(""),
+        Line::from("i: Hide or show the history panel"),
+        Line::from(""),
+        Line::from("x: Swap sides with the bot"),
         Line::from(""),
-        Line::from("`Esc`: Deselect a piece / hide popups"),
+        Line::from("f: Jump to a typed or pasted FEN position"),
+        Line::from(""),
+        Line::from("< >: Go back or forward in the moves of a solo game"),
+        Line::from(""),
+        Line::from("[ ]: Switch to the previous or next variation, m: make it the main line"),
         Line::from(""),
         Line::from("q: Quit the game"),
         Line::from(""),
@@ -234,6 +296,8 @@ pub fn render_help_popup(frame: &mut Frame) {
         Line::from(""),
         Line::from("Grey cell: Available cells for the selected piece"),
         Line::from(""),
+        Line::from("Arrow: First move of the chess engine's best line"),
+        Line::from(""),
         Line::from(""),
         Line::from("Press `Esc` to close the popup.").alignment(Alignment::Center),
     ];
@@ -0,0 +1,335 @@
+use crate::{
+    app::AppResult,
+    board::{Board, HistoryNotation, Orientation, Rotation},
+    error::ChessError,
+    theme::{CheckHighlight, PieceStyle, Theme},
+};
+use clap::ValueEnum;
+use ratatui::style::Color;
+use std::{fs, path::Path};
+
+/// Preferences remembered across sessions, written one `key = value` per line in the config file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub engine_path: Option<String>,
+    pub orientation: Orientation,
+    pub rotation: Rotation,
+    pub notation: HistoryNotation,
+    pub check_highlight: CheckHighlight,
+    pub piece_style: PieceStyle,
+    pub wide_glyphs: bool,
+    pub confirm_moves: bool,
+    pub auto_queen: bool,
+    pub remember_promotion_choice: bool,
+    pub explain_illegal_moves: bool,
+    pub bot_resigns: bool,
+    /// the fifty-move and repetition draws are left to the players
+    pub no_auto_draws: bool,
+    /// the engine comments the moves played against the bot
+    pub coach: bool,
+    /// colors of the theme, written like `light-blue`, `#646464` or an ANSI index
+    pub check_color: Color,
+    pub legal_move_color: Color,
+    pub cursor_color: Color,
+    pub selected_color: Color,
+    pub pending_move_color: Color,
+    pub pv_arrow_color: Color,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let theme = Theme::default();
+        Self {
+            engine_path: None,
+            orientation: Orientation::AlwaysWhite,
+            rotation: Rotation::default(),
+            notation: HistoryNotation::default(),
+            check_highlight: CheckHighlight::default(),
+            piece_style: PieceStyle::default(),
+            wide_glyphs: false,
+            confirm_moves: false,
+            auto_queen: false,
+            remember_promotion_choice: false,
+            explain_illegal_moves: false,
+            bot_resigns: false,
+            no_auto_draws: false,
+            coach: false,
+            check_color: theme.check_color,
+            legal_move_color: theme.legal_move_color,
+            cursor_color: theme.cursor_color,
+            selected_color: theme.selected_color,
+            pending_move_color: theme.pending_move_color,
+            pv_arrow_color: theme.pv_arrow_color,
+        }
+    }
+}
+
+impl Settings {
+    /// Read the settings written by `to_config`, the missing keys keep their default.
+    /// A line which can't be read is skipped with its error, its key keeps its default too.
+    /// A config holding only a path, as written by the older versions, is the engine path
+    pub fn from_config(config: &str) -> (Self, Vec<ChessError>) {
+        let mut settings = Self::default();
+        if !config.trim().is_empty() && !config.contains('=') {
+            settings.engine_path = Some(config.trim().to_string());
+            return (settings, vec![]);
+        }
+
+        let errors = config
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| settings.read_line(line).err())
+            .collect();
+        (settings, errors)
+    }
+
+    /// Set the setting of a `key = value` line
+    fn read_line(&mut self, line: &str) -> Result<(), ChessError> {
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ChessError::InvalidSetting(line.to_string()));
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "engine_path" => self.engine_path = (!value.is_empty()).then(|| value.to_string()),
+            "orientation" => self.orientation = parse_value(key, value)?,
+            "rotation" => self.rotation = parse_value(key, value)?,
+            "notation" => self.notation = parse_value(key, value)?,
+            "check_highlight" => self.check_highlight = parse_value(key, value)?,
+            "piece_style" => self.piece_style = parse_value(key, value)?,
+            "wide_glyphs" => self.wide_glyphs = parse_flag(key, value)?,
+            "confirm_moves" => self.confirm_moves = parse_flag(key, value)?,
+            "auto_queen" => self.auto_queen = parse_flag(key, value)?,
+            "remember_promotion_choice" => self.remember_promotion_choice = parse_flag(key, value)?,
+            "explain_illegal_moves" => self.explain_illegal_moves = parse_flag(key, value)?,
+            "bot_resigns" => self.bot_resigns = parse_flag(key, value)?,
+            "no_auto_draws" => self.no_auto_draws = parse_flag(key, value)?,
+            "coach" => self.coach = parse_flag(key, value)?,
+            "check_color" => self.check_color = parse_color(key, value)?,
+            "legal_move_color" => self.legal_move_color = parse_color(key, value)?,
+            "cursor_color" => self.cursor_color = parse_color(key, value)?,
+            "selected_color" => self.selected_color = parse_color(key, value)?,
+            "pending_move_color" => self.pending_move_color = parse_color(key, value)?,
+            "pv_arrow_color" => self.pv_arrow_color = parse_color(key, value)?,
+            _ => return Err(ChessError::InvalidSetting(format!("unknown key '{}'", key))),
+        }
+        Ok(())
+    }
+
+    pub fn to_config(&self) -> String {
+        [
+            ("engine_path", self.engine_path.clone().unwrap_or_default()),
+            ("orientation", value_name(self.orientation)),
+            ("rotation", value_name(self.rotation)),
+            ("notation", value_name(self.notation)),
+            ("check_highlight", value_name(self.check_highlight)),
+            ("piece_style", value_name(self.piece_style)),
+            ("wide_glyphs", self.wide_glyphs.to_string()),
+            ("confirm_moves", self.confirm_moves.to_string()),
+            ("auto_queen", self.auto_queen.to_string()),
+            (
+                "remember_promotion_choice",
+                self.remember_promotion_choice.to_string(),
+            ),
+            (
+                "explain_illegal_moves",
+                self.explain_illegal_moves.to_string(),
+            ),
+            ("bot_resigns", self.bot_resigns.to_string()),
+            ("no_auto_draws", self.no_auto_draws.to_string()),
+            ("coach", self.coach.to_string()),
+            ("check_color", color_name(self.check_color)),
+            ("legal_move_color", color_name(self.legal_move_color)),
+            ("cursor_color", color_name(self.cursor_color)),
+            ("selected_color", color_name(self.selected_color)),
+            ("pending_move_color", color_name(self.pending_move_color)),
+            ("pv_arrow_color", color_name(self.pv_arrow_color)),
+        ]
+        .iter()
+        .map(|(key, value)| format!("{} = {}\n", key, value))
+        .collect()
+    }
+
+    /// Settings of the config file with the errors of its lines, the defaults if there is none yet
+    pub fn load(path: &Path) -> AppResult<(Self, Vec<ChessError>)> {
+        if !path.exists() {
+            return Ok((Self::default(), vec![]));
+        }
+        Ok(Self::from_config(&fs::read_to_string(path)?))
+    }
+
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_config())?;
+        Ok(())
+    }
+
+    /// Give the settings to the board, the engine path goes to the app
+    pub fn apply(&self, board: &mut Board) {
+        board.orientation = self.orientation;
+        board.rotation = self.rotation;
+        board.history_notation = self.notation;
+        board.theme.check_highlight = self.check_highlight;
+        board.theme.ascii_only = self.piece_style == PieceStyle::Ascii;
+        board.theme.wide_glyphs = self.wide_glyphs;
+        board.confirm_moves = self.confirm_moves;
+        board.auto_queen = self.auto_queen;
+        board.remember_promotion_choice = self.remember_promotion_choice;
+        board.explain_illegal_moves = self.explain_illegal_moves;
+        board.bot_resigns_if_hopeless = self.bot_resigns;
+        board.draw_rules.fifty_moves = !self.no_auto_draws;
+        board.draw_rules.repetition = !self.no_auto_draws;
+        board.coach = self.coach;
+        board.theme.check_color = self.check_color;
+        board.theme.legal_move_color = self.legal_move_color;
+        board.theme.cursor_color = self.cursor_color;
+        board.theme.selected_color = self.selected_color;
+        board.theme.pending_move_color = self.pending_move_color;
+        board.theme.pv_arrow_color = self.pv_arrow_color;
+    }
+}
+
+/// Name of a value as given on the command line, like `follow-player`
+fn value_name(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Name of a color read back by `parse_color`
+fn color_name(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::Indexed(index) => index.to_string(),
+        _ => format!("{:?}", color),
+    }
+}
+
+fn parse_color(key: &str, value: &str) -> Result<Color, ChessError> {
+    value
+        .parse()
+        .map_err(|_| ChessError::InvalidSetting(format!("'{}' for {}", value, key)))
+}
+
+fn parse_value<T: ValueEnum>(key: &str, value: &str) -> Result<T, ChessError> {
+    T::from_str(value, true)
+        .map_err(|_| ChessError::InvalidSetting(format!("'{}' for {}", value, key)))
+}
+
+fn parse_flag(key: &str, value: &str) -> Result<bool, ChessError> {
+    value
+        .parse()
+        .map_err(|_| ChessError::InvalidSetting(format!("'{}' for {}", value, key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, Coords, HistoryNotation, Orientation, Rotation},
+        error::ChessError,
+        settings::Settings,
+        theme::{CheckHighlight, PieceStyle, Theme},
+    };
+    use ratatui::style::Color;
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = Settings {
+            engine_path: Some("/opt/homebrew/opt/stockfish".to_string()),
+            orientation: Orientation::AlwaysBlack,
+            rotation: Rotation::Deg90,
+            notation: HistoryNotation::San,
+            check_highlight: CheckHighlight::Solid,
+            piece_style: PieceStyle::Ascii,
+            wide_glyphs: false,
+            confirm_moves: true,
+            auto_queen: true,
+            remember_promotion_choice: true,
+            explain_illegal_moves: false,
+            bot_resigns: true,
+            no_auto_draws: true,
+            coach: true,
+            check_color: Color::Red,
+            legal_move_color: Color::Rgb(10, 200, 30),
+            cursor_color: Color::Indexed(208),
+            selected_color: Color::LightGreen,
+            pending_move_color: Color::Rgb(70, 130, 180),
+            pv_arrow_color: Color::DarkGray,
+        };
+        let config = settings.to_config();
+        assert!(config.contains("orientation = always-black\n"));
+        assert!(config.contains("legal_move_color = #0ac81e\n"));
+        assert_eq!(Settings::from_config(&config), (settings, vec![]));
+
+        let defaults = Settings::default();
+        assert_eq!(
+            Settings::from_config(&defaults.to_config()),
+            (defaults, vec![])
+        );
+    }
+
+    #[test]
+    fn settings_defaults_and_errors() {
+        assert_eq!(Settings::from_config(""), (Settings::default(), vec![]));
+        assert_eq!(
+            Settings::from_config("# only the notation\nnotation = FAN\n"),
+            (
+                Settings {
+                    notation: HistoryNotation::Fan,
+                    ..Default::default()
+                },
+                vec![]
+            )
+        );
+        // the config of the older versions only held the engine path
+        assert_eq!(
+            Settings::from_config("/usr/bin/stockfish").0.engine_path,
+            Some("/usr/bin/stockfish".to_string())
+        );
+
+        // the lines which can't be read keep their default, the others are read
+        let (settings, errors) = Settings::from_config(
+            "rotation = 45\nauto_queen = maybe\npiece_size = 3\nthe end\ncursor_color = sky\ncoach = true\n",
+        );
+        assert_eq!(
+            settings,
+            Settings {
+                coach: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(errors.len(), 5);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, ChessError::InvalidSetting(_))));
+    }
+
+    #[test]
+    fn settings_applied_to_the_board() {
+        let settings = Settings {
+            auto_queen: true,
+            piece_style: PieceStyle::Ascii,
+            rotation: Rotation::Deg180,
+            cursor_color: Color::Yellow,
+            ..Default::default()
+        };
+        let mut board = Board::from_fen("k7/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        settings.apply(&mut board);
+        assert!(board.theme.ascii_only);
+        assert_eq!(board.theme.cursor_color, Color::Yellow);
+        assert_eq!(board.theme.check_color, Theme::default().check_color);
+        assert_eq!(board.rotation, Rotation::Deg180);
+        assert_eq!(board.orientation, Orientation::AlwaysWhite);
+
+        // the pawn becomes a queen without the promotion popup
+        board.cursor_coordinates = Coords::new(1, 4);
+        board.select_cell();
+        board.cursor_coordinates = Coords::new(0, 4);
+        board.select_cell();
+        assert!(!board.is_promotion);
+        assert_eq!(board.to_uci_moves(), "e7e8q");
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    app::AppResult,
+    board::{Board, Coords, GameBoard},
+    constants::{BLACK, WHITE},
+    error::ChessError,
+    evaluation::uci_move_coords,
+    pieces::{PieceColor, PieceType},
+    theme::{rgb_color, Theme},
+};
+use ratatui::style::Color;
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Side of a cell in the SVG, in pixels
+const CELL_SIZE: usize = 45;
+
+/// Color of the SVG fill attribute, `none` for the terminal defaults
+fn svg_color(color: Color) -> String {
+    rgb_color(color).map_or("none".to_string(), |(r, g, b)| {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    })
+}
+
+/// SVG image of a position, White at the bottom, the cells of `last_move` highlighted.
+/// The pieces are drawn with the chess glyphs, or their FEN letter with `ascii_only`
+pub fn position_svg(
+    board: &GameBoard,
+    theme: &Theme,
+    last_move: Option<(Coords, Coords)>,
+) -> String {
+    let size = 8 * CELL_SIZE;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\">\n"
+    );
+
+    for (row, line) in board.iter().enumerate() {
+        for (col, piece) in line.iter().enumerate() {
+            let (x, y) = (col * CELL_SIZE, row * CELL_SIZE);
+            let cell_color = if (row + col) % 2 == 0 { WHITE } else { BLACK };
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{CELL_SIZE}\" height=\"{CELL_SIZE}\" fill=\"{}\"/>",
+                svg_color(cell_color)
+            );
+
+            let coords = Coords::new(row as i8, col as i8);
+            if last_move
+                .as_ref()
+                .is_some_and(|(from, to)| *from == coords || *to == coords)
+            {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"{CELL_SIZE}\" height=\"{CELL_SIZE}\" fill=\"{}\" fill-opacity=\"0.4\"/>",
+                    svg_color(theme.selected_color)
+                );
+            }
+
+            if let Some((piece_type, piece_color)) = piece {
+                // the filled glyph for both sides, the color comes from the fill
+                let glyph = if theme.ascii_only {
+                    PieceType::piece_to_fen_enum(Some(*piece_type), Some(*piece_color))
+                } else {
+                    PieceType::piece_to_utf_enum(*piece_type, Some(PieceColor::White))
+                };
+                let (fill, stroke) = match piece_color {
+                    PieceColor::White => ("#ffffff", "#000000"),
+                    PieceColor::Black => ("#000000", "#ffffff"),
+                };
+                let _ = writeln!(
+                    svg,
+                    "<text class=\"piece\" x=\"{}\" y=\"{}\" font-size=\"36\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{fill}\" stroke=\"{stroke}\" stroke-width=\"0.8\">{glyph}</text>",
+                    x + CELL_SIZE / 2,
+                    y + CELL_SIZE / 2
+                );
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// SVG image of each position of the game, from its start to the latest move
+pub fn game_frames(board: &Board) -> Result<Vec<String>, ChessError> {
+    let mut replay = match &board.starting_fen {
+        Some(fen) => Board::from_fen(fen)?,
+        None => Board::default(),
+    };
+    let mut frames = vec![position_svg(&replay.board(), &board.theme, None)];
+    for movement in board.to_uci_moves().split_whitespace() {
+        replay.play_uci_move(movement)?;
+        replay.switch_player_turn();
+        frames.push(position_svg(
+            &replay.board(),
+            &board.theme,
+            uci_move_coords(movement),
+        ));
+    }
+    Ok(frames)
+}
+
+/// Write the frames of the game as `000.svg`, `001.svg`... in `dir`, ready to be assembled in a GIF
+pub fn write_game_frames(board: &Board, dir: &Path) -> AppResult<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let mut paths = vec![];
+    for (ply, frame) in game_frames(board)?.iter().enumerate() {
+        let path = dir.join(format!("{:03}.svg", ply));
+        fs::write(&path, frame)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, Coords},
+        svg::{game_frames, position_svg, write_game_frames},
+        test_utils::temp_path,
+        theme::Theme,
+    };
+
+    #[test]
+    fn start_position_svg() {
+        let board = Board::default();
+        let svg = position_svg(&board.board(), &Theme::default(), None);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("class=\"piece\"").count(), 32);
+        assert_eq!(svg.matches("<rect").count(), 64);
+        // every element is closed
+        assert_eq!(svg.matches("<text").count(), svg.matches("</text>").count());
+    }
+
+    #[test]
+    fn game_svg_frames() {
+        let board = Board::from_uci_position("startpos moves e2e4 d7d5 e4d5").unwrap();
+        let frames = game_frames(&board).unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[3].matches("class=\"piece\"").count(), 31);
+        // the cells of the latest move are highlighted
+        assert_eq!(frames[0].matches("fill-opacity").count(), 0);
+        assert_eq!(frames[1].matches("fill-opacity").count(), 2);
+
+        let ascii = Theme {
+            ascii_only: true,
+            ..Default::default()
+        };
+        let svg = position_svg(
+            &board.board(),
+            &ascii,
+            Some((Coords::new(4, 4), Coords::new(3, 3))),
+        );
+        assert!(svg.contains(">P</text>") && svg.contains(">k</text>"));
+
+        let dir = temp_path("svg-frames");
+        let paths = write_game_frames(&board, &dir).unwrap();
+        assert_eq!(paths.len(), 4);
+        assert!(dir.join("003.svg").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
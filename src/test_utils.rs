@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+/// Path in the temporary directory named after the test process, so that parallel runs don't share it
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("chess-tui-{}-{}", std::process::id(), name))
+}
+
+/// Write an executable fake chess engine answering the UCI handshake, then running the shell lines of `rest`
+#[cfg(unix)]
+pub fn fake_engine(name: &str, rest: &str) -> PathBuf {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let engine_path = temp_path(name);
+    fs::write(
+        &engine_path,
+        format!("#!/bin/sh\necho started\nread uci\nread isready\necho readyok\n{rest}"),
+    )
+    .unwrap();
+    fs::set_permissions(&engine_path, fs::Permissions::from_mode(0o755)).unwrap();
+    engine_path
+}
@@ -0,0 +1,299 @@
+use crate::pieces::{PieceColor, PieceType};
+use clap::ValueEnum;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    widgets::BorderType,
+};
+
+/// How the cell of a checked king is highlighted
+#[derive(Debug, Copy, Clone, PartialEq, Default, ValueEnum)]
+pub enum CheckHighlight {
+    /// Blinking cell, some terminals render it as harsh flashing or ignore it
+    #[default]
+    Blink,
+    /// Same color without the blinking
+    Solid,
+    /// No highlight at all
+    Off,
+}
+
+/// How the pieces are drawn
+#[derive(Debug, Copy, Clone, PartialEq, Default, ValueEnum)]
+pub enum PieceStyle {
+    /// Chess glyphs
+    #[default]
+    Unicode,
+    /// FEN letters, for terminals mangling the chess glyphs
+    Ascii,
+}
+
+/// Colors and styles used to render the board
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub check_highlight: CheckHighlight,
+    pub check_color: Color,
+    /// cells the selected piece can move to
+    pub legal_move_color: Color,
+    pub cursor_color: Color,
+    pub selected_color: Color,
+    /// destination of the move waiting for a confirmation
+    pub pending_move_color: Color,
+    /// cells of the first move of the engine's principal variation
+    pub pv_arrow_color: Color,
+    /// whether the terminal supports RGB colors, they are replaced by the nearest ANSI color otherwise
+    pub truecolor: bool,
+    /// only use ASCII characters, for terminals mangling the chess glyphs and box drawing
+    pub ascii_only: bool,
+    /// the terminal draws the chess glyphs two cells wide
+    pub wide_glyphs: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            check_highlight: CheckHighlight::Blink,
+            check_color: Color::Magenta,
+            legal_move_color: Color::Rgb(100, 100, 100),
+            cursor_color: Color::LightBlue,
+            selected_color: Color::LightGreen,
+            pending_move_color: Color::Rgb(70, 130, 180),
+            pv_arrow_color: Color::Rgb(60, 160, 90),
+            truecolor: true,
+            ascii_only: false,
+            wide_glyphs: false,
+        }
+    }
+}
+
+impl Theme {
+    /// Style of the cell of a checked king, `None` if it should not be highlighted
+    pub fn check_style(&self) -> Option<Style> {
+        let style = Style::default().bg(self.color(self.check_color));
+        match self.check_highlight {
+            CheckHighlight::Blink => Some(style.add_modifier(Modifier::SLOW_BLINK)),
+            CheckHighlight::Solid => Some(style),
+            CheckHighlight::Off => None,
+        }
+    }
+
+    /// Whether the terminal can draw RGB colors, only a `COLORTERM` naming another color support says it can't
+    pub fn detect_truecolor() -> bool {
+        supports_truecolor(std::env::var("COLORTERM").ok().as_deref())
+    }
+
+    /// `color` as the terminal can draw it
+    pub fn color(&self, color: Color) -> Color {
+        match color {
+            Color::Rgb(r, g, b) if !self.truecolor => nearest_ansi_color(r, g, b),
+            _ => color,
+        }
+    }
+
+    /// Small symbol of a piece, used in the history and the promotion popup
+    pub fn piece_glyph(&self, piece_type: PieceType, piece_color: PieceColor) -> &'static str {
+        if self.ascii_only {
+            PieceType::piece_to_fen_enum(Some(piece_type), Some(piece_color))
+        } else {
+            PieceType::piece_to_utf_enum(piece_type, Some(piece_color))
+        }
+    }
+
+    /// Drawing of a piece on the board
+    pub fn piece_art(
+        &self,
+        piece_type: Option<PieceType>,
+        piece_color: Option<PieceColor>,
+    ) -> &'static str {
+        if self.ascii_only {
+            match piece_type {
+                Some(_) => PieceType::piece_to_fen_enum(piece_type, piece_color),
+                None => " ",
+            }
+        } else {
+            PieceType::piece_type_to_string_enum(piece_type)
+        }
+    }
+
+    /// Arrow pointing from the `from` cell to the `to` cell, both given as (row, col) on the screen
+    pub fn arrow_glyph(&self, from: (usize, usize), to: (usize, usize)) -> &'static str {
+        const ARROWS: [[&str; 3]; 3] = [["↖", "↑", "↗"], ["←", " ", "→"], ["↙", "↓", "↘"]];
+        const ASCII_ARROWS: [[&str; 3]; 3] = [["\\", "^", "/"], ["<", " ", ">"], ["/", "v", "\\"]];
+        let row = (to.0 as isize - from.0 as isize).signum() + 1;
+        let col = (to.1 as isize - from.1 as isize).signum() + 1;
+        if self.ascii_only {
+            ASCII_ARROWS[row as usize][col as usize]
+        } else {
+            ARROWS[row as usize][col as usize]
+        }
+    }
+
+    pub fn border_type(&self) -> BorderType {
+        if self.ascii_only {
+            BorderType::Plain
+        } else {
+            BorderType::Rounded
+        }
+    }
+}
+
+/// The ANSI colors with their RGB values in the usual xterm palette
+const ANSI_PALETTE: [(Color, (i32, i32, i32)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Whether RGB colors are drawn for this `COLORTERM`, when it is missing the RGB palette is kept
+fn supports_truecolor(colorterm: Option<&str>) -> bool {
+    match colorterm.map(str::trim) {
+        None | Some("") => true,
+        Some(colorterm) => colorterm == "truecolor" || colorterm == "24bit",
+    }
+}
+
+/// ANSI color closest to an RGB color, using the usual xterm palette
+pub fn nearest_ansi_color(r: u8, g: u8, b: u8) -> Color {
+    let (r, g, b) = (i32::from(r), i32::from(g), i32::from(b));
+
+    ANSI_PALETTE
+        .iter()
+        .min_by_key(|(_, (pr, pg, pb))| (r - pr).pow(2) + (g - pg).pow(2) + (b - pb).pow(2))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// RGB value of a color, the ANSI ones as in the xterm palette, `None` for the terminal defaults
+pub fn rgb_color(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        _ => ANSI_PALETTE
+            .iter()
+            .find(|(ansi_color, _)| *ansi_color == color)
+            .map(|(_, (r, g, b))| (*r as u8, *g as u8, *b as u8)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, Coords},
+        pieces::{PieceColor, PieceType},
+        theme::{nearest_ansi_color, supports_truecolor, CheckHighlight, Theme},
+        utils::{get_piece_color, get_piece_type},
+    };
+    use ratatui::{
+        style::{Color, Modifier},
+        widgets::BorderType,
+    };
+
+    #[test]
+    fn check_style_blink() {
+        let theme = Theme::default();
+
+        let style = theme.check_style().unwrap();
+        assert_eq!(style.bg, Some(Color::Magenta));
+        assert!(style.add_modifier.contains(Modifier::SLOW_BLINK));
+    }
+
+    #[test]
+    fn check_style_solid() {
+        let theme = Theme {
+            check_highlight: CheckHighlight::Solid,
+            ..Default::default()
+        };
+
+        let style = theme.check_style().unwrap();
+        assert_eq!(style.bg, Some(Color::Magenta));
+        assert!(!style.add_modifier.contains(Modifier::SLOW_BLINK));
+    }
+
+    #[test]
+    fn check_style_off() {
+        let theme = Theme {
+            check_highlight: CheckHighlight::Off,
+            ..Default::default()
+        };
+
+        assert_eq!(theme.check_style(), None);
+    }
+
+    #[test]
+    fn ascii_only_sample_position() {
+        let theme = Theme {
+            ascii_only: true,
+            ..Default::default()
+        };
+        let board =
+            Board::from_fen("r3k2r/ppp2ppp/2n1bn2/3qp3/3PP3/2N1BN2/PPP2PPP/R2QK2R w KQkq - 0 1")
+                .unwrap();
+
+        for i in 0..8 {
+            for j in 0..8 {
+                let coords = Coords::new(i, j);
+                let piece_type = get_piece_type(board.board(), &coords);
+                let piece_color = get_piece_color(board.board(), &coords);
+                assert!(theme.piece_art(piece_type, piece_color).is_ascii());
+                if let (Some(piece_type), Some(piece_color)) = (piece_type, piece_color) {
+                    assert!(theme.piece_glyph(piece_type, piece_color).is_ascii());
+                }
+            }
+        }
+        for piece_type in [
+            PieceType::Queen,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+        ] {
+            assert!(theme.piece_glyph(piece_type, PieceColor::Black).is_ascii());
+        }
+        assert_eq!(theme.border_type(), BorderType::Plain);
+    }
+
+    #[test]
+    fn nearest_ansi_color_mapping() {
+        assert_eq!(nearest_ansi_color(100, 100, 100), Color::DarkGray);
+        assert_eq!(nearest_ansi_color(160, 160, 160), Color::DarkGray);
+        assert_eq!(nearest_ansi_color(250, 10, 5), Color::LightRed);
+        assert_eq!(nearest_ansi_color(0, 0, 0), Color::Black);
+        assert_eq!(nearest_ansi_color(240, 240, 240), Color::Gray);
+    }
+
+    #[test]
+    fn color_fallback_without_truecolor() {
+        let theme = Theme {
+            truecolor: false,
+            ..Default::default()
+        };
+
+        assert_eq!(theme.color(theme.legal_move_color), Color::DarkGray);
+        // ANSI colors are kept as they are
+        assert_eq!(theme.color(Color::LightBlue), Color::LightBlue);
+        assert_eq!(
+            Theme::default().color(Color::Rgb(1, 2, 3)),
+            Color::Rgb(1, 2, 3)
+        );
+    }
+
+    #[test]
+    fn truecolor_detection() {
+        // most terminals don't set COLORTERM, they keep the RGB palette
+        assert!(supports_truecolor(None));
+        assert!(supports_truecolor(Some("")));
+        assert!(supports_truecolor(Some("truecolor")));
+        assert!(supports_truecolor(Some("24bit")));
+        assert!(!supports_truecolor(Some("8bit")));
+    }
+}
@@ -1,7 +1,7 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout},
     prelude::{Alignment, Rect},
-    style::{Style, Stylize},
+    style::{Color, Style, Stylize},
     text::Line,
     widgets::{Block, Paragraph},
     Frame,
@@ -10,9 +10,11 @@ use ratatui::{
 use crate::{
     app::App,
     constants::{Pages, TITLE},
+    evaluation::{render_evaluation_bar, Score},
     pieces::PieceColor,
     popups::{
-        render_credit_popup, render_end_popup, render_engine_path_error_popup, render_help_popup,
+        render_credit_popup, render_end_popup, render_engine_error_popup,
+        render_engine_path_error_popup, render_fen_input_popup, render_help_popup,
         render_promotion_popup,
     },
 };
@@ -24,7 +26,8 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     if app.current_page == Pages::Solo {
         render_game_ui(frame, app, main_area)
     } else if app.current_page == Pages::Bot {
-        if app.board.engine.is_none() {
+        // the engine is only started once, a stopped engine is handled by its own popup
+        if app.board.engine.is_none() && app.board.engine_path.is_none() {
             match &app.chess_engine_path {
                 Some(path) => {
                     app.board.set_engine(path);
@@ -118,6 +121,43 @@ pub fn render_menu_ui(frame: &mut Frame, app: &App, main_area: Rect) {
     frame.render_widget(sub_title, main_layout_horizontal[2])
 }
 
+/// Areas of the board, the evaluation bar, the history panel when it is shown and the help footer,
+/// the board takes the width of the hidden history panel. The footer has its own row at the bottom
+/// so it stays shown without the history panel
+pub fn game_layout(area: Rect, show_history_panel: bool) -> (Rect, Rect, Option<Rect>, Rect) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(area);
+
+    let constraints = if show_history_panel {
+        [
+            Constraint::Ratio(2, 17),
+            Constraint::Ratio(9, 17),
+            Constraint::Ratio(1, 17),
+            Constraint::Ratio(5, 17),
+        ]
+    } else {
+        [
+            Constraint::Ratio(1, 17),
+            Constraint::Ratio(14, 17),
+            Constraint::Ratio(1, 17),
+            Constraint::Ratio(1, 17),
+        ]
+    };
+    let main_layout_vertical = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints.as_ref())
+        .split(rows[0]);
+
+    (
+        main_layout_vertical[1],
+        main_layout_vertical[2],
+        show_history_panel.then_some(main_layout_vertical[3]),
+        rows[1],
+    )
+}
+
 // Method to render the game board and handle game popups
 pub fn render_game_ui(frame: &mut Frame, app: &App, main_area: Rect) {
     let main_layout_horizontal = Layout::default()
@@ -132,32 +172,64 @@ pub fn render_game_ui(frame: &mut Frame, app: &App, main_area: Rect) {
         )
         .split(main_area);
 
-    let main_layout_vertical = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(
-            [
-                Constraint::Ratio(2, 17),
-                Constraint::Ratio(9, 17),
-                Constraint::Ratio(1, 17),
-                Constraint::Ratio(5, 17),
-            ]
-            .as_ref(),
-        )
-        .split(main_layout_horizontal[1]);
+    let (board_area, evaluation_area, history_area, help_area) =
+        game_layout(main_layout_horizontal[1], app.show_history_panel);
 
     // Board block representing the full board div
     let board_block = Block::default().style(Style::default());
 
     // We render the board_block in the center layout made above
-    frame.render_widget(board_block.clone(), main_layout_vertical[1]);
+    frame.render_widget(board_block.clone(), board_area);
 
     // We make the inside of the board
-    app.board
-        .board_render(board_block.inner(main_layout_vertical[1]), frame);
+    app.board.board_render(board_block.inner(board_area), frame);
+
+    // The reason of the latest illegal move attempt, or the coach's comment, under the board
+    if let Some(reason) = app.board.illegal_move_reason {
+        let explanation = Paragraph::new(reason.to_string())
+            .alignment(Alignment::Center)
+            .fg(Color::LightRed);
+        frame.render_widget(explanation, main_layout_horizontal[2]);
+    } else if let Some(comment) = &app.board.coach_comment {
+        let comment = Paragraph::new(comment.as_str())
+            .alignment(Alignment::Center)
+            .fg(Color::LightCyan);
+        frame.render_widget(comment, main_layout_horizontal[2]);
+    }
+
+    // without a chess engine the bar shows the material balance
+    let score = match app.board.engine {
+        Some(_) => app.board.evaluation,
+        None => Some(Score::Centipawns(app.board.material_balance() * 100)),
+    };
+    if let Some(score) = score {
+        render_evaluation_bar(score, board_block.inner(evaluation_area), frame);
+    }
 
     // We make the inside of the board
-    app.board
-        .history_render(board_block.inner(main_layout_vertical[3]), frame);
+    if let Some(history_area) = history_area {
+        app.board
+            .history_render(board_block.inner(history_area), frame);
+
+        // the shown moves are a variation among others played from the same position
+        let (index, count) = app.analysis.variation();
+        if count > 1 && history_area.height > 3 {
+            let line_area = Rect::new(
+                history_area.x + 1,
+                history_area.bottom() - 3,
+                history_area.width.saturating_sub(2),
+                1,
+            );
+            let variation = Paragraph::new(format!("Variation {}/{}", index, count))
+                .alignment(Alignment::Center)
+                .fg(Color::Yellow);
+            frame.render_widget(variation, line_area);
+        }
+    }
+
+    // Bottom paragraph help text
+    let help_paragraph = Paragraph::new("Press ? for help").alignment(Alignment::Center);
+    frame.render_widget(help_paragraph, help_area);
 
     if app.board.is_promotion {
         render_promotion_popup(frame, app)
@@ -168,13 +240,63 @@ pub fn render_game_ui(frame: &mut Frame, app: &App, main_area: Rect) {
     }
 
     if app.board.is_checkmate {
-        let victorious_player = app.board.player_turn.opposite();
+        let victorious_player = app.board.player_turn().opposite();
 
         let string_color = match victorious_player {
             PieceColor::White => "White",
             PieceColor::Black => "Black",
         };
 
-        render_end_popup(frame, format!("{} Won !!!", string_color))
+        let sentence = match app.board.checkmate_pattern() {
+            Some(pattern) => format!("{} Won with a {} !!!", string_color, pattern),
+            None => format!("{} Won !!!", string_color),
+        };
+        render_end_popup(frame, sentence)
+    }
+
+    if let Some(resigned_player) = app.board.resigned {
+        let string_color = match resigned_player {
+            PieceColor::White => "White",
+            PieceColor::Black => "Black",
+        };
+
+        render_end_popup(frame, format!("{} resigned", string_color))
+    }
+
+    if let Some(error) = &app.board.engine_error {
+        render_engine_error_popup(frame, error)
+    }
+
+    if let Some(fen) = &app.fen_input {
+        render_fen_input_popup(frame, fen, app.fen_input_error.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::game_layout;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn game_layout_with_history_panel() {
+        let area = Rect::new(0, 0, 170, 40);
+
+        let (board_area, evaluation_area, history_area, help_area) = game_layout(area, true);
+        assert_eq!(board_area, Rect::new(20, 0, 90, 39));
+        assert_eq!(evaluation_area, Rect::new(110, 0, 10, 39));
+        assert_eq!(history_area, Some(Rect::new(120, 0, 50, 39)));
+        assert_eq!(help_area, Rect::new(0, 39, 170, 1));
+    }
+
+    #[test]
+    fn game_layout_without_history_panel() {
+        let area = Rect::new(0, 0, 170, 40);
+
+        let (board_area, evaluation_area, history_area, help_area) = game_layout(area, false);
+        assert_eq!(board_area, Rect::new(10, 0, 140, 39));
+        assert_eq!(evaluation_area, Rect::new(150, 0, 10, 39));
+        assert_eq!(history_area, None);
+        // the help footer doesn't go away with the history panel
+        assert_eq!(help_area, Rect::new(0, 39, 170, 1));
     }
 }
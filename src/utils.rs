@@ -3,13 +3,22 @@ use crate::{
     pieces::{PieceColor, PieceType},
 };
 use ratatui::style::Color;
+use unicode_width::UnicodeWidthChar;
+
+/// Piece at `coordinates`, `None` out of the board
+pub fn get_piece(board: GameBoard, coordinates: &Coords) -> Piece {
+    if !coordinates.is_valid() {
+        return None;
+    }
+    board[coordinates.row as usize][coordinates.col as usize]
+}
 
 pub fn get_piece_color(board: GameBoard, coordinates: &Coords) -> Option<PieceColor> {
-    board[coordinates.row as usize][coordinates.col as usize].map(|(_, piece_color)| piece_color)
+    get_piece(board, coordinates).map(|(_, piece_color)| piece_color)
 }
 
 pub fn get_piece_type(board: GameBoard, coordinates: &Coords) -> Option<PieceType> {
-    board[coordinates.row as usize][coordinates.col as usize].map(|(piece_type, _)| piece_type)
+    get_piece(board, coordinates).map(|(piece_type, _)| piece_type)
 }
 
 /// method to clean the position array to remove impossible positions
@@ -20,6 +29,49 @@ pub fn cleaned_positions(positions: Vec<Coords>) -> Vec<Coords> {
         .collect()
 }
 
+/// Cells reachable by a piece sliding along each of the (row, col) `directions` until it meets a piece
+///
+/// When `allow_move_on_ally_positions` is true we are computing protected cells:
+/// the ally pieces are included and the enemy king does not stop the slide
+pub fn slide(
+    coordinates: &Coords,
+    color: PieceColor,
+    board: GameBoard,
+    directions: &[(i8, i8)],
+    allow_move_on_ally_positions: bool,
+) -> Vec<Coords> {
+    let mut positions: Vec<Coords> = vec![];
+
+    for &(dy, dx) in directions {
+        for i in 1..8i8 {
+            // Invalid coords
+            let Some(new_coordinates) = coordinates.offset(dy * i, dx * i) else {
+                break;
+            };
+
+            if is_empty(board, &new_coordinates) {
+                positions.push(new_coordinates);
+                continue;
+            }
+            if is_enemy(board, &new_coordinates, color) {
+                let piece = board[new_coordinates.row as usize][new_coordinates.col as usize];
+                positions.push(new_coordinates);
+                if !allow_move_on_ally_positions || !is_piece_opposite_king(piece, color) {
+                    break;
+                }
+                continue;
+            }
+            // Ally cell
+            if allow_move_on_ally_positions {
+                positions.push(new_coordinates);
+            }
+            break;
+        }
+    }
+
+    positions
+}
+
 /// Return true for ally cell color; false for enemy
 pub fn is_cell_color_ally(board: GameBoard, coordinates: Coords, color: PieceColor) -> bool {
     match get_piece_color(board, &coordinates) {
@@ -28,6 +80,16 @@ pub fn is_cell_color_ally(board: GameBoard, coordinates: Coords, color: PieceCol
     }
 }
 
+/// Whether there is no piece on the cell
+pub fn is_empty(board: GameBoard, coordinates: &Coords) -> bool {
+    get_piece_color(board, coordinates).is_none()
+}
+
+/// Whether the cell holds a piece of the opponent of `color`
+pub fn is_enemy(board: GameBoard, coordinates: &Coords, color: PieceColor) -> bool {
+    get_piece_color(board, coordinates) == Some(color.opposite())
+}
+
 /// We get all the cells that are getting put in 'check'
 pub fn get_all_protected_cells(
     board: GameBoard,
@@ -119,6 +181,26 @@ pub fn chtoi(ch: Option<char>) -> i8 {
     }
 }
 
+/// Cells of a move of the history like `6444`, or `1404q` for a promotion,
+/// `None` if it isn't two cells of the board followed by an optional promotion piece
+pub fn parse_move_str(position: &str) -> Option<(Coords, Coords)> {
+    let chars: Vec<char> = position.chars().collect();
+    if !(4..=5).contains(&chars.len()) {
+        return None;
+    }
+    let cell = |row: char, col: char| {
+        let coords = Coords::new(row.to_digit(10)? as i8, col.to_digit(10)? as i8);
+        coords.is_valid().then_some(coords)
+    };
+    if chars
+        .get(4)
+        .is_some_and(|ch| PieceType::from_char(*ch).is_none())
+    {
+        return None;
+    }
+    Some((cell(chars[0], chars[1])?, cell(chars[2], chars[3])?))
+}
+
 pub fn get_latest_move(move_history: &[HistRec]) -> Option<HistRec> {
     if !move_history.is_empty() {
         return Some(move_history[move_history.len() - 1].clone());
@@ -130,16 +212,11 @@ pub fn did_piece_already_move(
     move_history: &[HistRec],
     original_piece: (PieceType, Coords),
 ) -> bool {
-    for entry in move_history {
-        let position = entry.1.clone();
-        let from_y = chtoi(position.chars().next());
-        let from_x = chtoi(position.chars().nth(1));
+    move_history.iter().any(|(piece_type, position)| {
         // Here there is an entry with the same piece type and the same original position, meaning it moved at some point
-        if entry.0 == original_piece.0 && Coords::new(from_y, from_x) == original_piece.1 {
-            return true;
-        }
-    }
-    false
+        *piece_type == original_piece.0
+            && parse_move_str(position).is_some_and(|(from, _)| from == original_piece.1)
+    })
 }
 // Method returning the coordinates of the king of a certain color
 pub fn get_king_coordinates(board: GameBoard, player_turn: PieceColor) -> Coords {
@@ -173,6 +250,37 @@ pub fn is_getting_checked(
     false
 }
 
+/// Pieces of the opponent of `color` attacking its king
+pub fn get_checkers(
+    board: GameBoard,
+    color: PieceColor,
+    move_history: &[HistRec],
+) -> Vec<(PieceType, Coords)> {
+    let king = get_king_coordinates(board, color);
+    let mut checkers = vec![];
+
+    for i in 0..8i8 {
+        for j in 0..8i8 {
+            let coords = Coords::new(i, j);
+            if let Some((piece_type, piece_color)) = board[i as usize][j as usize] {
+                if piece_color != color
+                    && PieceType::protected_positions(
+                        &coords,
+                        piece_type,
+                        piece_color,
+                        board,
+                        move_history,
+                    )
+                    .contains(&king)
+                {
+                    checkers.push((piece_type, coords));
+                }
+            }
+        }
+    }
+    checkers
+}
+
 pub fn impossible_positions_king_checked(
     original_coordinates: &Coords,
     positions: Vec<Coords>,
@@ -190,9 +298,9 @@ pub fn impossible_positions_king_checked(
 
         // We check if the board is still checked with this move meaning it didn't resolve the problem
         if !is_getting_checked(
-            new_board.board,
-            new_board.player_turn,
-            &new_board.move_history,
+            new_board.board(),
+            new_board.player_turn(),
+            new_board.move_history(),
         ) {
             cleaned_position.push(position)
         };
@@ -217,9 +325,83 @@ pub fn color_to_ratatui_enum(piece_color: Option<PieceColor>) -> Color {
     }
 }
 
+/// Number of terminal cells `text` takes, the chess symbols take two when `wide_chess_glyphs`
+pub fn display_width(text: &str, wide_chess_glyphs: bool) -> usize {
+    text.chars()
+        .map(|ch| {
+            if wide_chess_glyphs && ('\u{2654}'..='\u{265F}').contains(&ch) {
+                2
+            } else {
+                ch.width().unwrap_or(0)
+            }
+        })
+        .sum()
+}
+
+/// `text` followed by the spaces filling `width` cells
+pub fn pad_to_width(text: &str, width: usize, wide_chess_glyphs: bool) -> String {
+    let padding = width.saturating_sub(display_width(text, wide_chess_glyphs));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::utils::{convert_notation_into_position, convert_position_into_notation};
+    use crate::{
+        board::{Coords, GameBoard},
+        constants::ORTHOGONALS,
+        pieces::{PieceColor, PieceType},
+        utils::{
+            convert_notation_into_position, convert_position_into_notation, display_width,
+            is_cell_color_ally, is_empty, is_enemy, pad_to_width, parse_move_str, slide,
+        },
+    };
+
+    fn sample_board() -> GameBoard {
+        let mut board = [[None; 8]; 8];
+        board[4][4] = Some((PieceType::Rook, PieceColor::White));
+        board[4][6] = Some((PieceType::Pawn, PieceColor::Black));
+        board
+    }
+
+    #[test]
+    fn is_empty_cells() {
+        let board = sample_board();
+
+        assert!(is_empty(board, &Coords::new(0, 0)));
+        assert!(!is_empty(board, &Coords::new(4, 4)));
+        assert!(!is_empty(board, &Coords::new(4, 6)));
+    }
+
+    #[test]
+    fn is_enemy_cells() {
+        let board = sample_board();
+
+        assert!(is_enemy(board, &Coords::new(4, 6), PieceColor::White));
+        assert!(!is_enemy(board, &Coords::new(4, 4), PieceColor::White));
+        assert!(!is_enemy(board, &Coords::new(0, 0), PieceColor::White));
+        assert!(is_enemy(board, &Coords::new(4, 4), PieceColor::Black));
+    }
+
+    #[test]
+    fn is_ally_cells() {
+        let board = sample_board();
+
+        assert!(is_cell_color_ally(
+            board,
+            Coords::new(4, 4),
+            PieceColor::White
+        ));
+        assert!(!is_cell_color_ally(
+            board,
+            Coords::new(4, 6),
+            PieceColor::White
+        ));
+        assert!(!is_cell_color_ally(
+            board,
+            Coords::new(0, 0),
+            PieceColor::White
+        ));
+    }
 
     #[test]
     fn convert_position_into_notation_1() {
@@ -243,4 +425,99 @@ mod tests {
     fn convert_notation_into_position_3() {
         assert_eq!(convert_notation_into_position("g1f3".to_string()), "7655")
     }
+
+    #[test]
+    fn slide_protected_cells_go_through_the_opposite_king() {
+        let custom_board = [
+            [
+                Some((PieceType::Rook, PieceColor::White)),
+                None,
+                Some((PieceType::King, PieceColor::Black)),
+                None,
+                Some((PieceType::Knight, PieceColor::White)),
+                None,
+                None,
+                None,
+            ],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+            [None, None, None, None, None, None, None, None],
+        ];
+
+        // Moving: the rook stops on the king
+        let positions = slide(
+            &Coords::new(0, 0),
+            PieceColor::White,
+            custom_board,
+            &ORTHOGONALS[3..],
+            false,
+        );
+        assert_eq!(positions, vec![Coords::new(0, 1), Coords::new(0, 2)]);
+
+        // Protecting: the rook goes through the king and protects its ally
+        let positions = slide(
+            &Coords::new(0, 0),
+            PieceColor::White,
+            custom_board,
+            &ORTHOGONALS[3..],
+            true,
+        );
+        assert_eq!(
+            positions,
+            vec![
+                Coords::new(0, 1),
+                Coords::new(0, 2),
+                Coords::new(0, 3),
+                Coords::new(0, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn display_width_of_glyphs() {
+        assert_eq!(display_width("♞ g1-f3", false), 7);
+        assert_eq!(display_width("♞ g1-f3", true), 8);
+        assert_eq!(display_width("N g1-f3", true), 7);
+        // wide characters are always measured as such
+        assert_eq!(display_width("象", false), 2);
+    }
+
+    #[test]
+    fn pad_to_width_aligns_columns() {
+        for wide in [false, true] {
+            let knight = pad_to_width("♞ g1-f3", 10, wide);
+            let pawn = pad_to_width("e2-e4", 10, wide);
+            assert_eq!(display_width(&knight, wide), 10);
+            assert_eq!(display_width(&pawn, wide), 10);
+        }
+        assert_eq!(pad_to_width("♞f3", 5, false), "♞f3  ");
+        assert_eq!(pad_to_width("♞f3", 5, true), "♞f3 ");
+        // too long cells are left as they are
+        assert_eq!(pad_to_width("Nxe4+", 3, false), "Nxe4+");
+    }
+
+    #[test]
+    fn parse_valid_move_str() {
+        assert_eq!(
+            parse_move_str("6444"),
+            Some((Coords::new(6, 4), Coords::new(4, 4)))
+        );
+        assert_eq!(
+            parse_move_str("1404q"),
+            Some((Coords::new(1, 4), Coords::new(0, 4)))
+        );
+    }
+
+    #[test]
+    fn parse_malformed_move_str() {
+        for position in [
+            "", "644", "64444", "6448", "e2e4", "6-44", "1404x", "14049", "６444",
+        ] {
+            assert_eq!(parse_move_str(position), None, "{position}");
+        }
+    }
 }